@@ -27,7 +27,9 @@ use actix_session::{storage::CookieSessionStore, SessionMiddleware};
 use actix_web::cookie::Key;
 use actix_web::cookie::SameSite;
 use actix_web::http::header::HeaderValue;
-use actix_web::http::header::{CONTENT_SECURITY_POLICY, STRICT_TRANSPORT_SECURITY};
+use actix_web::http::header::{
+    CONTENT_DISPOSITION, CONTENT_SECURITY_POLICY, STRICT_TRANSPORT_SECURITY,
+};
 use actix_web::{http::StatusCode, ResponseError};
 use actix_web::{
     middleware, web, App, Error as AWError, HttpRequest, HttpResponse, HttpServer, Result,
@@ -36,10 +38,17 @@ use actix_web_flash_messages::storage::CookieMessageStore;
 use actix_web_flash_messages::FlashMessagesFramework;
 
 use libhc::dbpostgres::HcDbPostgres;
+use libhc::formindex::FormIndex;
 //use libhc::dbsqlite::HcDbSqlite;
+use libhc::protocol::StatusResponse;
+use libhc::protocol::WsResponseFrame;
 use libhc::AnswerQuery;
 use libhc::AskQuery;
+use libhc::ChatQuery;
+use libhc::ChatResponse;
 use libhc::CreateSessionQuery;
+use libhc::DisputeQuery;
+use libhc::ExportQuery;
 use libhc::GetMoveQuery;
 use libhc::GetMovesQuery;
 use libhc::GetSessions;
@@ -47,6 +56,11 @@ use libhc::HcError;
 use libhc::HcGreekVerb;
 use libhc::MoveResult;
 use libhc::MoveType;
+use libhc::ParseAnswerQuery;
+use libhc::RemoveChatQuery;
+use libhc::ResolveDisputeQuery;
+use libhc::SpectateQuery;
+use libhc::UserStatsQuery;
 use thiserror::Error;
 
 use actix::Actor;
@@ -89,13 +103,6 @@ struct GetMovesResponse {
     success: bool,
 }
 
-#[derive(Serialize)]
-pub struct StatusResponse {
-    response_to: String,
-    mesg: String,
-    success: bool,
-}
-
 /// Entry point for our websocket route
 async fn ws_route(
     req: HttpRequest,
@@ -107,6 +114,7 @@ async fn ws_route(
         //println!("uuid {:?}", uuid);
         let db = req.app_data::<HcDbPostgres>().unwrap();
         let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+        let form_index = req.app_data::<Arc<FormIndex>>().unwrap();
         let username = login::get_username(session);
         ws::start(
             session::WsHcGameSession {
@@ -116,6 +124,7 @@ async fn ws_route(
                 name: None,
                 addr: srv.get_ref().clone(),
                 verbs: verbs.clone(),
+                form_index: form_index.clone(),
                 db: db.clone(),
                 username,
             },
@@ -159,7 +168,8 @@ async fn get_sessions(
     if let Some(user_id) = login::get_user_id(session.clone()) {
         let username = login::get_username(session);
 
-        let res = libhc::hc_get_sessions(db, user_id, verbs, username, &info)
+        let timestamp = libhc::get_timestamp();
+        let res = libhc::hc_get_sessions(db, user_id, verbs, username, &info, timestamp)
             .await
             .map_err(map_hc_error)?;
         Ok(HttpResponse::Ok().json(res))
@@ -189,6 +199,51 @@ async fn get_game_moves(
     }
 }
 
+async fn export_transcript(
+    (info, req, session): (web::Query<ExportQuery>, HttpRequest, Session),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_get_transcript(db, user_id, info.session_id, verbs)
+            .await
+            .map_err(map_hc_error)?;
+
+        if info.format.as_deref() == Some("csv") {
+            Ok(HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header((
+                    CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"transcript-{}.csv\"",
+                        info.session_id
+                    ),
+                ))
+                .body(libhc::hc_transcript_to_csv(&res)))
+        } else {
+            Ok(HttpResponse::Ok().json(res))
+        }
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn get_user_stats(
+    (info, session, req): (web::Form<UserStatsQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_get_user_stats(db, user_id, &info)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
 async fn create_session(
     (session, info, req): (Session, web::Form<CreateSessionQuery>, HttpRequest),
 ) -> Result<HttpResponse, AWError> {
@@ -198,16 +253,13 @@ async fn create_session(
     if let Some(user_id) = login::get_user_id(session) {
         let timestamp = libhc::get_timestamp();
 
-        let (mesg, success) =
-            match libhc::hc_insert_session(db, user_id, &info, verbs, timestamp).await {
-                Ok(_session_uuid) => (String::from("inserted!"), true),
-                Err(HcError::UnknownError) => (String::from("opponent not found!"), false),
-                Err(e) => (format!("error inserting: {e:?}"), false),
-            };
+        let _session_uuid = libhc::hc_insert_session(db, user_id, &info, verbs, timestamp)
+            .await
+            .map_err(map_hc_error)?;
         let res = StatusResponse {
             response_to: String::from("newsession"),
-            mesg,
-            success,
+            mesg: String::from("inserted!"),
+            success: true,
         };
         Ok(HttpResponse::Ok().json(res))
     } else {
@@ -234,16 +286,59 @@ async fn get_move(
     }
 }
 
+async fn start_answer(
+    (info, req, session): (web::Form<GetMoveQuery>, HttpRequest, Session),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        libhc::hc_start_answer(db, user_id, info.session_id, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+        let res = StatusResponse {
+            response_to: String::from("startanswer"),
+            mesg: String::from("started"),
+            success: true,
+        };
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
 async fn enter(
     (info, req, session): (web::Form<AnswerQuery>, HttpRequest, Session),
 ) -> Result<HttpResponse, AWError> {
     let db = req.app_data::<HcDbPostgres>().unwrap();
     let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+    let form_index = req.app_data::<Arc<FormIndex>>().map(|fi| fi.as_ref());
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_answer(db, user_id, &info, timestamp, verbs, form_index)
+            .await
+            .map_err(map_hc_error)?;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn parse_answer(
+    (info, req, session): (web::Form<ParseAnswerQuery>, HttpRequest, Session),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+    let form_index = req.app_data::<Arc<FormIndex>>().map(|fi| fi.as_ref());
 
     let timestamp = libhc::get_timestamp();
 
     if let Some(user_id) = login::get_user_id(session) {
-        let res = libhc::hc_answer(db, user_id, &info, timestamp, verbs)
+        let res = libhc::hc_answer_parse(db, user_id, &info, timestamp, verbs, form_index)
             .await
             .map_err(map_hc_error)?;
 
@@ -291,6 +386,322 @@ async fn mf(
     }
 }
 
+async fn resign(
+    (info, req, session, srv): (
+        web::Form<GetMoveQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_resign(db, user_id, info.session_id, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        notify_game_room(db, verbs, &srv, user_id, info.session_id).await;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn dispute_move(
+    (info, session, req): (web::Form<DisputeQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let timestamp = libhc::get_timestamp();
+        let res = libhc::hc_dispute_move(db, user_id, &info, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn resolve_dispute(
+    (info, req, session, srv): (
+        web::Form<ResolveDisputeQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let timestamp = libhc::get_timestamp();
+        let res = libhc::hc_resolve_dispute(db, user_id, &info, timestamp, verbs)
+            .await
+            .map_err(map_hc_error)?;
+
+        //scores may have changed
+        notify_game_room(db, verbs, &srv, user_id, res.dispute.session_id).await;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn get_disputes(
+    (info, session, req): (web::Form<GetMoveQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_get_disputes(db, user_id, info.session_id)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn send_chat(
+    (info, req, session, srv): (
+        web::Form<ChatQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let timestamp = libhc::get_timestamp();
+        let res = libhc::hc_send_chat(db, user_id, &info, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        notify_chat(&srv, user_id, &res);
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn get_chat(
+    (info, session, req): (web::Form<GetMoveQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_get_chat(db, user_id, info.session_id)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn remove_chat(
+    (info, req, session, srv): (
+        web::Form<RemoveChatQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let timestamp = libhc::get_timestamp();
+        let res = libhc::hc_remove_chat(db, user_id, &info, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        //players in the game drop the message from their chat
+        notify_chat(&srv, user_id, &res);
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn share_session(
+    (info, session, req): (web::Form<GetMoveQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_share_session(db, user_id, info.session_id)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+//without a websocket a spectator polls this for the game's state
+async fn spectate(
+    (info, session, req): (web::Form<SpectateQuery>, Session, HttpRequest),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_spectate(db, user_id, &info, verbs)
+            .await
+            .map_err(map_hc_error)?;
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn abandon(
+    (info, req, session, srv): (
+        web::Form<GetMoveQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_abandon(db, user_id, info.session_id, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        notify_game_room(db, verbs, &srv, user_id, info.session_id).await;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn accept_invitation(
+    (info, req, session, srv): (
+        web::Form<GetMoveQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_accept_invitation(db, user_id, info.session_id, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        notify_challenger(db, verbs, &srv, user_id, info.session_id).await;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+async fn decline_invitation(
+    (info, req, session, srv): (
+        web::Form<GetMoveQuery>,
+        HttpRequest,
+        Session,
+        web::Data<Addr<server::HcGameServer>>,
+    ),
+) -> Result<HttpResponse, AWError> {
+    let db = req.app_data::<HcDbPostgres>().unwrap();
+    let verbs = req.app_data::<Vec<Arc<HcGreekVerb>>>().unwrap();
+
+    let timestamp = libhc::get_timestamp();
+
+    if let Some(user_id) = login::get_user_id(session) {
+        let res = libhc::hc_decline_invitation(db, user_id, info.session_id, timestamp)
+            .await
+            .map_err(map_hc_error)?;
+
+        notify_challenger(db, verbs, &srv, user_id, info.session_id).await;
+
+        Ok(HttpResponse::Ok().json(res))
+    } else {
+        not_logged_in_response()
+    }
+}
+
+//let the challenger know their invitation was answered, even if they are not in the game's room
+pub async fn notify_challenger(
+    db: &HcDbPostgres,
+    verbs: &[Arc<HcGreekVerb>],
+    srv: &Addr<server::HcGameServer>,
+    user_id: Uuid,
+    session_id: Uuid,
+) {
+    if let Ok(Some(challenger_id)) = libhc::hc_get_opponent(db, user_id, session_id).await {
+        if let Ok(mut res) = libhc::hc_get_move(db, user_id, true, session_id, verbs).await {
+            res.response_to = String::from("invitationresponse");
+            if let Ok(resjson) = serde_json::to_string(&WsResponseFrame::new(None, res)) {
+                srv.do_send(server::UserMessage {
+                    id: challenger_id,
+                    msg: resjson,
+                });
+            }
+        }
+    }
+}
+
+//send the opponent's view of the game to the game's websocket room, and the spectators' view to anyone watching
+pub async fn notify_game_room(
+    db: &HcDbPostgres,
+    verbs: &[Arc<HcGreekVerb>],
+    srv: &Addr<server::HcGameServer>,
+    user_id: Uuid,
+    session_id: Uuid,
+) {
+    if let Ok(res) = libhc::hc_get_move(db, user_id, true, session_id, verbs).await {
+        if let Ok(resjson) = serde_json::to_string(&WsResponseFrame::new(None, res)) {
+            srv.do_send(server::ClientMessage {
+                id: user_id,
+                msg: resjson,
+                room: session_id,
+            });
+        }
+    }
+    if let Ok(res) = libhc::hc_get_spectator_state(db, session_id, verbs).await {
+        if let Ok(resjson) = serde_json::to_string(&WsResponseFrame::new(None, res)) {
+            srv.do_send(server::SpectatorMessage {
+                msg: resjson,
+                room: session_id,
+            });
+        }
+    }
+}
+
+//deliver a chat message, or its removal, to the rest of the game's websocket room
+pub fn notify_chat(srv: &Addr<server::HcGameServer>, user_id: Uuid, res: &ChatResponse) {
+    if let Ok(resjson) = serde_json::to_string(&WsResponseFrame::new(None, res.clone())) {
+        srv.do_send(server::ClientMessage {
+            id: user_id,
+            msg: resjson,
+            room: res.chat.session_id,
+        });
+    }
+}
+
 fn not_logged_in_response() -> Result<HttpResponse, AWError> {
     Ok(HttpResponse::Unauthorized().finish())
 }
@@ -333,22 +744,33 @@ impl ResponseError for PhilologusError {
 }
 
 pub fn map_hc_error(e: HcError) -> PhilologusError {
-    match e {
-        HcError::Database(e) => PhilologusError {
-            code: StatusCode::INTERNAL_SERVER_ERROR,
-            name: String::from("sqlx error"),
-            error: format!("sqlx Configuration: {}", e),
-        },
-        HcError::AuthenticationError => PhilologusError {
-            code: StatusCode::INTERNAL_SERVER_ERROR,
-            name: String::from("authentication error"),
-            error: String::from("authentication error"),
-        },
-        HcError::UnknownError => PhilologusError {
-            code: StatusCode::INTERNAL_SERVER_ERROR,
-            name: String::from("unknown error"),
-            error: String::from("unknown error"),
-        },
+    let code = match e {
+        HcError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        HcError::AuthenticationError => StatusCode::UNAUTHORIZED,
+        HcError::NotParticipant => StatusCode::FORBIDDEN,
+        HcError::OutOfTurn
+        | HcError::AlreadyAnswered
+        | HcError::GameOver
+        | HcError::InvitationNotAccepted
+        | HcError::InvitationNotPending
+        | HcError::InvalidDispute(_) => StatusCode::CONFLICT,
+        HcError::NotAuthorized => StatusCode::FORBIDDEN,
+        HcError::SessionNotFound
+        | HcError::OpponentNotFound
+        | HcError::MoveNotFound
+        | HcError::DisputeNotFound
+        | HcError::ChatMessageNotFound
+        | HcError::ClassNotFound => StatusCode::NOT_FOUND,
+        HcError::SelfChallenge
+        | HcError::InvalidSessionConfig(_)
+        | HcError::InvalidChatMessage(_)
+        | HcError::InvalidClassCode(_) => StatusCode::BAD_REQUEST,
+        HcError::UnknownError => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    PhilologusError {
+        code,
+        name: String::from(e.code()),
+        error: e.to_string(),
     }
 }
 
@@ -416,6 +838,11 @@ async fn main() -> io::Result<()> {
         .await
         .expect("Error creating database");
 
+    //for recognizing what a wrong answer actually is
+    let form_index = Arc::new(libhc::formindex::hc_build_form_index(
+        &libhc::hc_load_verbs("pp.txt"),
+    ));
+
     //1. to make a new key:
     // let secret_key = Key::generate(); // only for testing: should use same key from .env file/variable, else have to login again on each restart
     // println!("key: {}{}", hex::encode( secret_key.signing() ), hex::encode( secret_key.encryption() ));
@@ -458,6 +885,7 @@ async fn main() -> io::Result<()> {
                 google_oauth: oauth_google_client,
             })
             .app_data(libhc::hc_load_verbs("pp.txt"))
+            .app_data(form_index.clone())
             .app_data(hcdb.clone())
             .app_data(web::Data::from(app_state.clone()))
             .app_data(web::Data::new(server.clone()))
@@ -510,13 +938,29 @@ fn config(cfg: &mut web::ServiceConfig) {
         //.route("/ws", web::get().to(ws_route))
         .service(web::resource("/ws").route(web::get().to(ws_route)))
         .service(web::resource("/healthzzz").route(web::get().to(health_check)))
+        .service(web::resource("/startanswer").route(web::post().to(start_answer)))
         .service(web::resource("/enter").route(web::post().to(enter)))
         .service(web::resource("/new").route(web::post().to(create_session)))
         .service(web::resource("/list").route(web::post().to(get_sessions)))
         .service(web::resource("/getmove").route(web::post().to(get_move)))
         .service(web::resource("/getgamemoves").route(web::post().to(get_game_moves)))
+        .service(web::resource("/stats").route(web::post().to(get_user_stats)))
+        .service(web::resource("/export").route(web::get().to(export_transcript)))
         .service(web::resource("/ask").route(web::post().to(ask)))
         .service(web::resource("/mf").route(web::post().to(mf)))
+        .service(web::resource("/parseanswer").route(web::post().to(parse_answer)))
+        .service(web::resource("/resign").route(web::post().to(resign)))
+        .service(web::resource("/abandon").route(web::post().to(abandon)))
+        .service(web::resource("/acceptinvitation").route(web::post().to(accept_invitation)))
+        .service(web::resource("/declineinvitation").route(web::post().to(decline_invitation)))
+        .service(web::resource("/dispute").route(web::post().to(dispute_move)))
+        .service(web::resource("/resolvedispute").route(web::post().to(resolve_dispute)))
+        .service(web::resource("/disputes").route(web::post().to(get_disputes)))
+        .service(web::resource("/chat").route(web::post().to(send_chat)))
+        .service(web::resource("/getchat").route(web::post().to(get_chat)))
+        .service(web::resource("/removechat").route(web::post().to(remove_chat)))
+        .service(web::resource("/share").route(web::post().to(share_session)))
+        .service(web::resource("/spectate").route(web::post().to(spectate)))
         .service(
             fs::Files::new("/", "./static")
                 .prefer_utf8(true)
@@ -3,6 +3,10 @@
 //! room through `HcGameServer`.
 
 use actix::prelude::*;
+use libhc::protocol::PresenceResponse;
+use libhc::protocol::TypingResponse;
+use libhc::protocol::WsResponse;
+use libhc::protocol::WsResponseFrame;
 use sqlx::types::Uuid;
 use std::{
     collections::{HashMap, HashSet},
@@ -45,6 +49,46 @@ pub struct ClientMessage {
     pub room: Uuid,
 }
 
+/// Send message to a specific user, whatever room they are in
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UserMessage {
+    /// Id of the receiving user
+    pub id: Uuid,
+    /// Peer message
+    pub msg: String,
+}
+
+/// Send message to everyone watching a game
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SpectatorMessage {
+    /// Peer message
+    pub msg: String,
+    /// Room name
+    pub room: Uuid,
+}
+
+/// Tell the rest of a game room whether a player is answering
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Typing {
+    /// Id of the typing user
+    pub user_uuid: Uuid,
+    /// Room name
+    pub game_uuid: Uuid,
+    pub typing: bool,
+}
+
+/// Which of these users are connected
+pub struct Online {
+    pub users: Vec<Uuid>,
+}
+
+impl actix::Message for Online {
+    type Result = HashSet<Uuid>;
+}
+
 /// List of available rooms
 pub struct ListRooms;
 
@@ -63,6 +107,18 @@ pub struct Join {
     pub game_uuid: Uuid,
 }
 
+/// Watch a game: spectators are kept apart from the players' room,
+/// so they get the game's state but not the players' own messages
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SpectatorJoin {
+    /// Client ID
+    pub user_uuid: Uuid,
+
+    /// Room name
+    pub game_uuid: Uuid,
+}
+
 /// `HcGameServer` manages game rooms and responsible for coordinating game session.
 ///
 /// Implementation is very naïve.
@@ -70,6 +126,7 @@ pub struct Join {
 pub struct HcGameServer {
     sessions: HashMap<Uuid, Recipient<Message>>,
     rooms: HashMap<Uuid, HashSet<Uuid>>,
+    spectators: HashMap<Uuid, HashSet<Uuid>>,
     #[allow(dead_code)]
     visitor_count: Arc<AtomicUsize>,
 }
@@ -83,6 +140,7 @@ impl HcGameServer {
         HcGameServer {
             sessions: HashMap::new(),
             rooms,
+            spectators: HashMap::new(),
             visitor_count,
         }
     }
@@ -106,6 +164,28 @@ impl HcGameServer {
             }
         }
     }
+
+    /// Stop watching whatever game a user was watching
+    fn leave_spectating(&mut self, user_id: Uuid) {
+        for spectators in self.spectators.values_mut() {
+            spectators.remove(&user_id);
+        }
+        self.spectators.retain(|_, s| !s.is_empty());
+    }
+
+    /// Push a message to the room's other users, framed as a response nobody asked for
+    fn push(&self, room: Uuid, res: impl Into<WsResponse>, skip_id: Uuid) {
+        if let Ok(resjson) = serde_json::to_string(&WsResponseFrame::new(None, res)) {
+            self.send_message(room, &resjson, skip_id);
+        }
+    }
+
+    /// Tell the other players in a game room that a player came or went
+    fn send_presence(&self, room: Uuid, user_id: Uuid, present: bool) {
+        if room != MAIN_ROOM {
+            self.push(room, PresenceResponse::new(room, present), user_id);
+        }
+    }
 }
 
 /// Make actor from `HcGameServer`
@@ -165,11 +245,12 @@ impl Handler<Disconnect> for HcGameServer {
                     rooms.push(name.to_owned());
                 }
             }
+            self.leave_spectating(msg.id);
         }
         // send message to other users
-        // for room in rooms {
-        //     self.send_message(room, "Someone disconnected", 0);
-        // }
+        for room in rooms {
+            self.send_presence(room, msg.id, false);
+        }
     }
 }
 
@@ -182,6 +263,17 @@ impl Handler<ClientMessage> for HcGameServer {
     }
 }
 
+/// Handler for `UserMessage` message.
+impl Handler<UserMessage> for HcGameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: UserMessage, _: &mut Context<Self>) {
+        if let Some(addr) = self.sessions.get(&msg.id) {
+            addr.do_send(Message(msg.msg));
+        }
+    }
+}
+
 /// Handler for `ListRooms` message.
 impl Handler<ListRooms> for HcGameServer {
     type Result = MessageResult<ListRooms>;
@@ -215,10 +307,13 @@ impl Handler<Join> for HcGameServer {
                 rooms.push(n.to_owned());
             }
         }
+        self.leave_spectating(user_uuid);
         // send message to other users
-        // for room in rooms {
-        //     self.send_message(&room, "Someone disconnected", 0);
-        // }
+        for room in &rooms {
+            if *room != game_uuid {
+                self.send_presence(*room, user_uuid, false);
+            }
+        }
 
         //println!("joined room: {:?}, id: {:?}", name, id);
 
@@ -228,6 +323,99 @@ impl Handler<Join> for HcGameServer {
             .or_default()
             .insert(user_uuid);
 
-        //self.send_message(&name, "Someone connected", id);
+        // only announce a new arrival: the client rejoins the same room on every getmove
+        if !rooms.contains(&game_uuid) && game_uuid != MAIN_ROOM {
+            self.send_presence(game_uuid, user_uuid, true);
+
+            // and let the newcomer know who is already here
+            if let (Some(others), Some(addr)) =
+                (self.rooms.get(&game_uuid), self.sessions.get(&user_uuid))
+            {
+                if others.iter().any(|id| *id != user_uuid) {
+                    let res = WsResponseFrame::new(None, PresenceResponse::new(game_uuid, true));
+                    if let Ok(resjson) = serde_json::to_string(&res) {
+                        addr.do_send(Message(resjson));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Handler for `Typing` message.
+impl Handler<Typing> for HcGameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: Typing, _: &mut Context<Self>) {
+        // only relay for players who are actually in the room
+        if self
+            .rooms
+            .get(&msg.game_uuid)
+            .is_some_and(|r| r.contains(&msg.user_uuid))
+        {
+            self.push(
+                msg.game_uuid,
+                TypingResponse::new(msg.game_uuid, msg.typing),
+                msg.user_uuid,
+            );
+        }
+    }
+}
+
+/// Handler for `Online` message.
+impl Handler<Online> for HcGameServer {
+    type Result = MessageResult<Online>;
+
+    fn handle(&mut self, msg: Online, _: &mut Context<Self>) -> Self::Result {
+        MessageResult(
+            msg.users
+                .into_iter()
+                .filter(|id| self.sessions.contains_key(id))
+                .collect(),
+        )
+    }
+}
+
+/// Handler for `SpectatorMessage` message.
+impl Handler<SpectatorMessage> for HcGameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: SpectatorMessage, _: &mut Context<Self>) {
+        if let Some(spectators) = self.spectators.get(&msg.room) {
+            for id in spectators {
+                if let Some(addr) = self.sessions.get(id) {
+                    addr.do_send(Message(msg.msg.to_owned()));
+                }
+            }
+        }
+    }
+}
+
+/// Leave any game room as a player and start watching the game
+impl Handler<SpectatorJoin> for HcGameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: SpectatorJoin, _: &mut Context<Self>) {
+        let SpectatorJoin {
+            user_uuid,
+            game_uuid,
+        } = msg;
+        let mut rooms = Vec::new();
+
+        // a spectator stays in the main room, but leaves any game they were playing
+        for (n, sessions) in &mut self.rooms {
+            if *n != MAIN_ROOM && sessions.remove(&user_uuid) {
+                rooms.push(n.to_owned());
+            }
+        }
+        for room in rooms {
+            self.send_presence(room, user_uuid, false);
+        }
+
+        self.leave_spectating(user_uuid);
+        self.spectators
+            .entry(game_uuid)
+            .or_default()
+            .insert(user_uuid);
     }
 }
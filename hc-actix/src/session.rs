@@ -3,14 +3,17 @@ use actix::prelude::*;
 use actix_web_actors::ws;
 use std::time::{Duration, Instant};
 
-use crate::GetMoveQuery;
 use uuid::Uuid;
 
-use crate::GetSessions;
 use crate::HcDbPostgres;
 use crate::MoveType;
-use crate::StatusResponse;
-use libhc::HcDb;
+use libhc::formindex::FormIndex;
+use libhc::protocol::hc_parse_ws_request;
+use libhc::protocol::StatusResponse;
+use libhc::protocol::WsErrorFrame;
+use libhc::protocol::WsRequest;
+use libhc::protocol::WsResponse;
+use libhc::protocol::WsResponseFrame;
 use libhc::HcError;
 use libhc::HcGreekVerb;
 use std::sync::Arc;
@@ -38,6 +41,7 @@ pub struct WsHcGameSession {
 
     pub addr: Addr<server::HcGameServer>,
     pub verbs: Vec<Arc<HcGreekVerb>>,
+    pub form_index: Arc<FormIndex>,
     pub db: HcDbPostgres,
     pub username: Option<String>,
 }
@@ -67,6 +71,22 @@ impl WsHcGameSession {
     }
 }
 
+//answer a request with its response, or an error frame if it failed, tagged with the request's id
+async fn send_reply<T: Into<WsResponse>>(
+    addr: &Addr<WsHcGameSession>,
+    id: Option<u64>,
+    qtype: &str,
+    res: Result<T, HcError>,
+) {
+    let resjson = match res {
+        Ok(res) => serde_json::to_string(&WsResponseFrame::new(id, res)),
+        Err(e) => serde_json::to_string(&WsErrorFrame::from_hc_error(id, qtype, &e)),
+    };
+    if let Ok(resjson) = resjson {
+        let _ = addr.send(server::Message(resjson)).await;
+    }
+}
+
 impl Actor for WsHcGameSession {
     type Context = ws::WebsocketContext<Self>;
 
@@ -136,11 +156,15 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsHcGameSession {
                 self.hb = Instant::now();
             }
             ws::Message::Text(text) => {
-                let m = text.trim();
-                let msg = if let Some(ref name) = self.name {
-                    format!("{name}: {m}")
-                } else {
-                    m.to_owned()
+                let frame = match hc_parse_ws_request(text.trim()) {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        //tell the client what was wrong rather than dropping the message
+                        if let Ok(resjson) = serde_json::to_string(&e) {
+                            ctx.text(resjson);
+                        }
+                        return;
+                    }
                 };
 
                 //https://stackoverflow.com/questions/64434912/how-to-correctly-call-async-functions-in-a-websocket-handler-in-actix-web
@@ -148,41 +172,35 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsHcGameSession {
                 //https://github.com/agmcleod/sc-predictions-server/blob/dev/server/src/websocket/client_messages.rs
                 let db = self.db.clone();
                 let verbs = self.verbs.clone();
+                let form_index = self.form_index.clone();
                 let user_id = self.id;
-                //let oid = self.id.clone();
-                //let room = self.room.clone();
                 let addr = ctx.address(); //self.addr.clone();
+                let addr2 = self.addr.clone();
                 let timestamp = libhc::get_timestamp();
                 let username = self.username.clone();
-                if msg.contains("getmove") {
-                    if let Ok(info) = serde_json::from_str::<GetMoveQuery>(&msg) {
-                        //join game room
-                        self.addr.do_send(server::Join {
-                            user_uuid: user_id,
-                            game_uuid: info.session_id,
-                        });
+                let id = frame.id;
+                let qtype = frame.request.qtype();
+                match frame.request {
+                    WsRequest::GetMove(info) => {
                         let fut = async move {
-                            let mut tx = db.begin_tx().await.unwrap();
-                            if let Ok(res) = libhc::hc_get_move_tr(
-                                &mut tx,
-                                user_id,
-                                false,
-                                info.session_id,
-                                &verbs,
-                            )
-                            .await
+                            //join game room, so only the players are announced to each other
+                            if let Ok(true) =
+                                libhc::hc_is_participant(&db, user_id, info.session_id).await
                             {
-                                tx.commit_tx().await.unwrap();
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
-                                }
+                                addr2.do_send(server::Join {
+                                    user_uuid: user_id,
+                                    game_uuid: info.session_id,
+                                });
                             }
+                            let res =
+                                libhc::hc_get_move(&db, user_id, false, info.session_id, &verbs)
+                                    .await;
+                            send_reply(&addr, id, qtype, res).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
-                } else if msg.contains("newsession") {
-                    if let Ok(info) = serde_json::from_str(&msg) {
+                    WsRequest::NewSession(info) => {
                         let fut = async move {
                             let (mesg, success) = match libhc::hc_insert_session(
                                 &db, user_id, &info, &verbs, timestamp,
@@ -190,199 +208,260 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsHcGameSession {
                             .await
                             {
                                 Ok(_session_uuid) => (String::from("inserted!"), true),
-                                Err(HcError::UnknownError) => {
+                                Err(HcError::OpponentNotFound) => {
                                     (String::from("opponent not found!"), false)
                                 }
-                                Err(e) => (format!("error inserting: {e:?}"), false),
+                                Err(HcError::SelfChallenge) => {
+                                    (String::from("you cannot challenge yourself!"), false)
+                                }
+                                Err(e) => (format!("error inserting: {e}"), false),
                             };
                             let res = StatusResponse {
                                 response_to: String::from("newsession"),
                                 mesg,
                                 success,
                             };
-                            if let Ok(resjson) = serde_json::to_string(&res) {
-                                let _ = addr.send(server::Message(resjson)).await;
-                            }
+                            send_reply(&addr, id, qtype, Ok(res)).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
-                } else if msg.contains("ask") {
-                    if let Ok(info) = serde_json::from_str(&msg) {
-                        let addr2 = self.addr.clone();
+                    WsRequest::Ask(info) => {
                         let fut = async move {
-                            if let Ok(res) =
-                                libhc::hc_ask(&db, user_id, &info, timestamp, &verbs).await
-                            {
+                            let res = libhc::hc_ask(&db, user_id, &info, timestamp, &verbs).await;
+                            if let Ok(ref res) = res {
                                 if res.move_type != MoveType::Practice {
-                                    let gm = GetMoveQuery {
-                                        qtype: String::from("getmove"),
-                                        session_id: info.session_id,
-                                    };
-                                    let mut tx = db.begin_tx().await.unwrap();
-                                    if let Ok(gm_res) = libhc::hc_get_move_tr(
-                                        &mut tx,
-                                        user_id,
-                                        true,
-                                        gm.session_id,
+                                    crate::notify_game_room(
+                                        &db,
                                         &verbs,
+                                        &addr2,
+                                        user_id,
+                                        info.session_id,
                                     )
-                                    .await
-                                    {
-                                        tx.commit_tx().await.unwrap();
-                                        if let Ok(gm_resjson) = serde_json::to_string(&gm_res) {
-                                            //println!("send to room {:?}", info.session_id);
-                                            addr2.do_send(server::ClientMessage {
-                                                id: user_id,
-                                                msg: gm_resjson,
-                                                room: info.session_id,
-                                            });
-                                        }
-                                    }
-                                }
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
+                                    .await;
                                 }
                             }
+                            send_reply(&addr, id, qtype, res).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
-                } else if msg.contains("submit") {
-                    if let Ok(info) = serde_json::from_str(&msg) {
-                        let addr2 = self.addr.clone();
+                    WsRequest::StartAnswer(info) => {
                         let fut = async move {
-                            if let Ok(res) =
-                                libhc::hc_answer(&db, user_id, &info, timestamp, &verbs).await
-                            {
+                            let res =
+                                libhc::hc_start_answer(&db, user_id, info.session_id, timestamp)
+                                    .await
+                                    .map(|_| StatusResponse {
+                                        response_to: String::from("startanswer"),
+                                        mesg: String::from("started"),
+                                        success: true,
+                                    });
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::Submit(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_answer(
+                                &db,
+                                user_id,
+                                &info,
+                                timestamp,
+                                &verbs,
+                                Some(&form_index),
+                            )
+                            .await;
+                            if let Ok(ref res) = res {
                                 if res.move_type != MoveType::Practice {
-                                    let gm = GetMoveQuery {
-                                        qtype: String::from("getmove"),
-                                        session_id: info.session_id,
-                                    };
-                                    let mut tx = db.begin_tx().await.unwrap();
-                                    if let Ok(gm_res) = libhc::hc_get_move_tr(
-                                        &mut tx,
-                                        user_id,
-                                        true,
-                                        gm.session_id,
+                                    crate::notify_game_room(
+                                        &db,
                                         &verbs,
+                                        &addr2,
+                                        user_id,
+                                        info.session_id,
                                     )
-                                    .await
-                                    {
-                                        tx.commit_tx().await.unwrap();
-                                        if let Ok(gm_resjson) = serde_json::to_string(&gm_res) {
-                                            //println!("send to room {:?}", info.session_id);
-                                            addr2.do_send(server::ClientMessage {
-                                                id: user_id,
-                                                msg: gm_resjson,
-                                                room: info.session_id,
-                                            });
-                                        }
-                                    }
-                                }
-
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
+                                    .await;
                                 }
                             }
+                            send_reply(&addr, id, qtype, res).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
-                } else if msg.contains("mfpressed") {
-                    if let Ok(info) = serde_json::from_str(&msg) {
-                        let addr2 = self.addr.clone();
+                    WsRequest::MfPressed(info) => {
                         let fut = async move {
-                            if let Ok(res) =
-                                libhc::hc_mf_pressed(&db, user_id, &info, timestamp, &verbs).await
-                            {
+                            let res =
+                                libhc::hc_mf_pressed(&db, user_id, &info, timestamp, &verbs).await;
+                            if let Ok(ref res) = res {
                                 if res.move_type != MoveType::Practice
                                     && res.is_correct == Some(false)
                                 {
-                                    let gm = GetMoveQuery {
-                                        qtype: String::from("getmove"),
-                                        session_id: info.session_id,
-                                    };
-                                    let mut tx = db.begin_tx().await.unwrap();
-                                    if let Ok(gm_res) = libhc::hc_get_move_tr(
-                                        &mut tx,
-                                        user_id,
-                                        true,
-                                        gm.session_id,
+                                    crate::notify_game_room(
+                                        &db,
                                         &verbs,
+                                        &addr2,
+                                        user_id,
+                                        info.session_id,
                                     )
-                                    .await
-                                    {
-                                        tx.commit_tx().await.unwrap();
-                                        if let Ok(gm_resjson) = serde_json::to_string(&gm_res) {
-                                            //println!("send to room {:?}", info.session_id);
-                                            addr2.do_send(server::ClientMessage {
-                                                id: user_id,
-                                                msg: gm_resjson,
-                                                room: info.session_id,
-                                            });
-                                        }
-                                    }
-                                }
-
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
+                                    .await;
                                 }
                             }
+                            send_reply(&addr, id, qtype, res).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
-                } else if msg.contains("getsessions") {
-                    if let Ok(info) = serde_json::from_str::<GetSessions>(&msg) {
-                        if let Some(session_id) = info.current_session {
-                            //join game room
-                            self.addr.do_send(server::Join {
-                                user_uuid: user_id,
-                                game_uuid: session_id,
-                            });
-                        }
+                    WsRequest::ParseAnswer(info) => {
+                        //parsing is practice only, so only the sender needs the result
                         let fut = async move {
-                            /*
-                            let current_session = match info.current_session {
-                                Some(r) => {
-                                    match libhc::hc_get_move(&db, user_id, false, r, &verbs)
-                                        .await
-                                    {
-                                        Ok(res) => Some(res),
-                                        Err(_) => None,
-                                    }
-                                }
-                                _ => None,
-                            };
-                            let mut tx = db.begin_tx().await.unwrap();
-                            if let Ok(sessions) = libhc::hc_get_sessions_tr(&mut tx, user_id).await
-                            {
-                                let res = SessionsListResponse {
-                                    response_to: String::from("getsessions"),
-                                    sessions,
-                                    success: true,
-                                    username,
-                                    current_session,
-                                };
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
+                            let res = libhc::hc_answer_parse(
+                                &db,
+                                user_id,
+                                &info,
+                                timestamp,
+                                &verbs,
+                                Some(&form_index),
+                            )
+                            .await;
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::GetSessions(info) => {
+                        let fut = async move {
+                            if let Some(session_id) = info.current_session {
+                                //join game room
+                                if let Ok(true) =
+                                    libhc::hc_is_participant(&db, user_id, session_id).await
+                                {
+                                    addr2.do_send(server::Join {
+                                        user_uuid: user_id,
+                                        game_uuid: session_id,
+                                    });
                                 }
                             }
-                            tx.commit_tx().await.unwrap();
-                            */
-                            if let Ok(res) =
-                                libhc::hc_get_sessions(&db, user_id, &verbs, username, &info).await
-                            {
-                                if let Ok(resjson) = serde_json::to_string(&res) {
-                                    let _ = addr.send(server::Message(resjson)).await;
+                            let mut res = libhc::hc_get_sessions(
+                                &db, user_id, &verbs, username, &info, timestamp,
+                            )
+                            .await;
+                            if let Ok(ref mut res) = res {
+                                let users = res.sessions.iter().filter_map(|s| s.opponent_id());
+                                let online = addr2
+                                    .send(server::Online {
+                                        users: users.collect(),
+                                    })
+                                    .await
+                                    .unwrap_or_default();
+                                for s in &mut res.sessions {
+                                    s.opponent_online =
+                                        s.opponent_id().map(|o| online.contains(&o));
                                 }
                             }
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::Typing(info) => {
+                        //relayed as is: typing indicators are too frequent to answer each one
+                        self.addr.do_send(server::Typing {
+                            user_uuid: user_id,
+                            game_uuid: info.session_id,
+                            typing: info.typing,
+                        });
+                    }
+                    WsRequest::Chat(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_send_chat(&db, user_id, &info, timestamp).await;
+                            if let Ok(ref res) = res {
+                                crate::notify_chat(&addr2, user_id, res);
+                            }
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::GetChat(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_get_chat(&db, user_id, info.session_id).await;
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::RemoveChat(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_remove_chat(&db, user_id, &info, timestamp).await;
+                            if let Ok(ref res) = res {
+                                crate::notify_chat(&addr2, user_id, res);
+                            }
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::Share(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_share_session(&db, user_id, info.session_id).await;
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::Spectate(info) => {
+                        let fut = async move {
+                            let res = libhc::hc_spectate(&db, user_id, &info, &verbs).await;
+                            //watch the game only once we know the user may
+                            if res.is_ok() {
+                                addr2.do_send(server::SpectatorJoin {
+                                    user_uuid: user_id,
+                                    game_uuid: info.session_id,
+                                });
+                            }
+                            send_reply(&addr, id, qtype, res).await;
+                        };
+                        let fut = actix::fut::wrap_future::<_, Self>(fut);
+                        ctx.spawn(fut);
+                    }
+                    WsRequest::AcceptInvitation(info) | WsRequest::DeclineInvitation(info) => {
+                        let accept = qtype == "acceptinvitation";
+                        let fut = async move {
+                            let res = if accept {
+                                libhc::hc_accept_invitation(
+                                    &db,
+                                    user_id,
+                                    info.session_id,
+                                    timestamp,
+                                )
+                                .await
+                            } else {
+                                libhc::hc_decline_invitation(
+                                    &db,
+                                    user_id,
+                                    info.session_id,
+                                    timestamp,
+                                )
+                                .await
+                            };
+                            if res.is_ok() {
+                                crate::notify_challenger(
+                                    &db,
+                                    &verbs,
+                                    &addr2,
+                                    user_id,
+                                    info.session_id,
+                                )
+                                .await;
+                            }
+                            send_reply(&addr, id, qtype, res).await;
                         };
                         let fut = actix::fut::wrap_future::<_, Self>(fut);
                         ctx.spawn(fut);
                     }
+                    //hc_parse_ws_request already answered unknown qtypes with an error
+                    WsRequest::Unknown => (),
                 }
             }
             ws::Message::Binary(_) => println!("Unexpected binary"),
@@ -17,16 +17,24 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::AnswerGrade;
 use crate::AnswerQuery;
 use crate::AskQuery;
+use crate::ChatResult;
 use crate::CreateSessionQuery;
+use crate::DisputeResult;
+use crate::DisputeStatus;
 use crate::HcDb;
 use crate::HcError;
+use crate::LeaderboardRow;
+use crate::MasteryResult;
 use crate::MoveResult;
 use crate::MoveType;
 use crate::SessionResult;
+use crate::SessionStatus;
 use crate::SessionsListQuery;
 use crate::UserResult;
+use crate::USER_TYPE_INSTRUCTOR;
 use secrecy::ExposeSecret;
 use secrecy::Secret;
 use sqlx::types::Uuid;
@@ -37,7 +45,11 @@ use sqlx::postgres::PgRow;
 use sqlx::Postgres;
 use sqlx::Transaction;
 
+use crate::synopsis::GreekParadigmResult;
+use crate::synopsis::GreekPpResult;
 use crate::synopsis::GreekSynopsisResult;
+use crate::synopsis::PpAnswerResult;
+use crate::synopsis::SynopsisJsonResult;
 use crate::synopsis::SynopsisSaverRequest;
 
 fn map_sqlx_error(err: sqlx::Error) -> HcError {
@@ -84,6 +96,40 @@ impl HcDb for HcDbPostgres {
     }
 }
 
+impl HcDbPostgresTrx<'_> {
+    //CREATE TABLE IF NOT EXISTS leaves an existing table as it is, so columns added later are added here
+    async fn add_column(
+        &mut self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), HcError> {
+        let query = format!("ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {column} {definition};");
+        let _res = sqlx::query(&query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    //for columns whose type changed since an earlier version
+    async fn alter_column_type(
+        &mut self,
+        table: &str,
+        column: &str,
+        definition: &str,
+    ) -> Result<(), HcError> {
+        let query = format!("ALTER TABLE {table} ALTER COLUMN {column} TYPE {definition};");
+        let _res = sqlx::query(&query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl HcTrx for HcDbPostgresTrx<'_> {
     async fn commit_tx(self: Box<Self>) -> Result<(), HcError> {
@@ -145,7 +191,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         let ip = "";
         let agent = "";
         let uuid = sqlx::types::Uuid::new_v4();
-        let query = format!("INSERT INTO greeksynopsisresults VALUES ($1, $2, DEFAULT, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, '{}')",
+        let query = format!("INSERT INTO greeksynopsisresults VALUES ($1, $2, DEFAULT, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, '{}', $19)",
             info.r.join("', '"));
         //println!("aaa: {}", query);
         sqlx::query(&query)
@@ -167,7 +213,112 @@ impl HcTrx for HcDbPostgresTrx<'_> {
             .bind(ip)
             .bind(agent)
             .bind(1)
-            .bind("0")
+            .bind(&info.score)
+            .bind(&info.grades)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+    async fn greek_get_paradigm_list(
+        &mut self,
+        user_id: Option<Uuid>,
+    ) -> Result<Vec<(Uuid, chrono::NaiveDateTime, Option<String>, String, String)>, HcError> {
+        let query = "SELECT id, updated, sname, advisor, selectedverb FROM greekparadigmresults WHERE user_id = $1 ORDER BY updated DESC;";
+
+        let res: Vec<(Uuid, chrono::NaiveDateTime, Option<String>, String, String)> =
+            sqlx::query_as(query)
+                .bind(user_id)
+                .fetch_all(&mut *self.tx)
+                .await
+                .map_err(map_sqlx_error)?;
+        Ok(res)
+    }
+
+    async fn greek_get_paradigm_result(
+        &mut self,
+        id: Uuid,
+    ) -> Result<GreekParadigmResult, HcError> {
+        let query = "SELECT * FROM greekparadigmresults WHERE id = $1;";
+        let res: GreekParadigmResult = sqlx::query_as(query)
+            .bind(id)
+            .fetch_one(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn greek_insert_paradigm(
+        &mut self,
+        user_id: Option<Uuid>,
+        res: &SynopsisJsonResult,
+        grades: &str,
+        score: &str,
+    ) -> Result<(), HcError> {
+        let query = "INSERT INTO greekparadigmresults (id, user_id, sname, advisor, sgiday, selectedverb, pp, pp_correct, pp_is_correct, verbtense, verbvoice, verbmood, ip, ua, status, score, grades, \
+            f0, a0, c0, f1, a1, c1, f2, a2, c2, f3, a3, c3, f4, a4, c4, f5, a5, c5) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, \
+            $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35);";
+        let mut q = sqlx::query(query)
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(&res.name)
+            .bind(&res.advisor)
+            .bind(res.unit)
+            .bind(res.verb_id.to_string())
+            .bind(&res.pp)
+            .bind(&res.pp_correct)
+            .bind(&res.pp_is_correct)
+            .bind(res.tense)
+            .bind(res.voice)
+            .bind(res.mood)
+            .bind("")
+            .bind("")
+            .bind(1)
+            .bind(score)
+            .bind(grades);
+        for f in res.f.iter() {
+            q = q.bind(&f.given).bind(&f.correct).bind(f.is_correct);
+        }
+        q.execute(&mut *self.tx).await.map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn greek_get_pp_results(&mut self, user_id: Uuid) -> Result<Vec<GreekPpResult>, HcError> {
+        let query = "SELECT * FROM greekppresults WHERE user_id = $1 ORDER BY updated DESC;";
+        let res: Vec<GreekPpResult> = sqlx::query_as(query)
+            .bind(user_id)
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+        Ok(res)
+    }
+
+    async fn greek_insert_pp_result(
+        &mut self,
+        user_id: Option<Uuid>,
+        res: &PpAnswerResult,
+    ) -> Result<(), HcError> {
+        let query = "INSERT INTO greekppresults (id, user_id, verb_id, unit, pp, pp_correct, pp_is_correct, score) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8);";
+        sqlx::query(query)
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(res.verb_id)
+            .bind(res.unit)
+            .bind(&res.pp)
+            .bind(&res.pp_correct)
+            .bind(
+                res.pp_is_correct
+                    .iter()
+                    .map(|c| (*c as i32).to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+            .bind(res.score)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
@@ -228,10 +379,11 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         &mut self,
         session_id: Uuid,
         user_to_score: &str,
-        points: i32,
+        points: f64,
     ) -> Result<(), HcError> {
+        //kept to a tenth of a point so partial-credit scores add up and compare exactly
         let query = format!(
-            "UPDATE sessions SET {user_to_score} = {user_to_score} + $1 WHERE session_id = $2;"
+            "UPDATE sessions SET {user_to_score} = ROUND(({user_to_score} + $1)::NUMERIC, 1) WHERE session_id = $2;"
         );
         let _res = sqlx::query(&query)
             .bind(points)
@@ -243,9 +395,9 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         Ok(())
     }
 
-    async fn get_user_id(&mut self, username: &str) -> Result<UserResult, HcError> {
+    async fn get_user_id(&mut self, username: &str) -> Result<Option<UserResult>, HcError> {
         let query = "SELECT user_id,user_name,password,email,user_type,timestamp FROM users WHERE user_name = $1 LIMIT 1;";
-        let res: UserResult = sqlx::query(query)
+        let res: Option<UserResult> = sqlx::query(query)
             .bind(username)
             .map(|rec: PgRow| UserResult {
                 user_id: rec.get("user_id"),
@@ -255,7 +407,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                 user_type: rec.get("user_type"),
                 timestamp: rec.get("timestamp"),
             })
-            .fetch_one(&mut *self.tx)
+            .fetch_optional(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
@@ -268,6 +420,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         highest_unit: Option<i16>,
         opponent_id: Option<Uuid>,
         info: &CreateSessionQuery,
+        status: SessionStatus,
         timestamp: i64,
     ) -> Result<Uuid, HcError> {
         let uuid = sqlx::types::Uuid::new_v4();
@@ -287,9 +440,17 @@ impl HcTrx for HcDbPostgresTrx<'_> {
             practice_reps_per_verb,
             countdown,
             max_time,
+            win_score,
+            max_rounds,
+            end_on_verbs_exhausted,
+            adaptive,
+            partial_credit,
+            parsing,
+            multiple_choice,
+            nonfinite,
             timestamp,
             updated,
-            status) VALUES ($1,$2,$3,NULL,$4,$5,$6,$7,$8,0,0,$9,$10,$11,$12,$13,1);"#;
+            status) VALUES ($1,$2,$3,NULL,$4,$5,$6,$7,$8,0,0,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20,$21,$22);"#;
         let _res = sqlx::query(query)
             .bind(uuid)
             .bind(user_id)
@@ -302,8 +463,17 @@ impl HcTrx for HcDbPostgresTrx<'_> {
             .bind(info.practice_reps_per_verb)
             .bind(info.countdown as i32)
             .bind(info.max_time)
+            .bind(info.win_score)
+            .bind(info.max_rounds)
+            .bind(info.end_on_verbs_exhausted.map(|e| e as i32))
+            .bind(info.adaptive.map(|a| a as i32))
+            .bind(info.partial_credit.map(|p| p as i32))
+            .bind(info.parsing.map(|p| p as i32))
+            .bind(info.multiple_choice.map(|m| m as i32))
+            .bind(info.nonfinite.map(|n| n as i32))
             .bind(timestamp)
             .bind(timestamp)
+            .bind(status.to_i32())
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
@@ -336,10 +506,18 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                 answer: rec.get("answer"),
                 correct_answer: rec.get("correct_answer"),
                 is_correct: rec.get("is_correct"),
+                grade: rec
+                    .get::<Option<i32>, &str>("grade")
+                    .map(AnswerGrade::from_i32),
+                score: rec.get("score"),
                 time: rec.get("time"),
                 timed_out: rec.get("timed_out"),
                 mf_pressed: rec.get("mf_pressed"),
+                gender: rec.get("ptcgender"),
+                case: rec.get("ptccase"),
+                choices: rec.get("choices"),
                 asktimestamp: rec.get("asktimestamp"),
+                answerstarttimestamp: rec.get("answerstarttimestamp"),
                 answeredtimestamp: rec.get("answeredtimestamp"),
             })
             .fetch_all(&mut *self.tx)
@@ -357,12 +535,12 @@ impl HcTrx for HcDbPostgresTrx<'_> {
 
         //strftime('%Y-%m-%d %H:%M:%S', DATETIME(timestamp, 'unixepoch')) as timestamp,
         //    ORDER BY updated DESC \
-        let query = "SELECT session_id AS session_id, name, challenged_user_id AS challenged, b.user_name AS username, challenger_score as myscore, challenged_score as theirscore, \
-        a.timestamp as timestamp, a.updated as updated, countdown, max_time, max_changes \
+        let query = "SELECT session_id AS session_id, name, challenged_user_id AS challenged, b.user_name AS username, b.user_id AS opponent_id, challenger_score as myscore, challenged_score as theirscore, \
+        a.timestamp as timestamp, a.updated as updated, countdown, max_time, max_changes, nonfinite, status, resigned_user_id \
         FROM sessions a LEFT JOIN users b ON a.challenged_user_id = b.user_id \
         where challenger_user_id = $1 \
-        UNION SELECT session_id AS session_id, name, challenged_user_id AS challenged, b.user_name AS username, challenged_score as myscore, challenger_score as theirscore, \
-        a.timestamp as timestamp, a.updated as updated, countdown, max_time, max_changes \
+        UNION SELECT session_id AS session_id, name, challenged_user_id AS challenged, b.user_name AS username, b.user_id AS opponent_id, challenged_score as myscore, challenger_score as theirscore, \
+        a.timestamp as timestamp, a.updated as updated, countdown, max_time, max_changes, nonfinite, status, resigned_user_id \
         FROM sessions a LEFT JOIN users b ON a.challenger_user_id = b.user_id \
         where challenged_user_id  = $2 \
         ORDER BY updated DESC \
@@ -378,6 +556,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                     name: rec.get("name"),
                     challenged: rec.get("challenged"), /*opponent:rec.get("opponent_user_id"),*/
                     opponent_name: rec.get("username"),
+                    opponent_online: None,
                     timestamp: rec.get("timestamp"),
                     updated: rec.get("updated"),
                     myturn: false,
@@ -387,6 +566,11 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                     countdown: rec.get("countdown"),
                     max_time: rec.get("max_time"),
                     max_changes: rec.get("max_changes"),
+                    nonfinite: rec.get("nonfinite"),
+                    status: SessionStatus::from_i32(rec.get("status")),
+                    game_result: None,
+                    resigned_user_id: rec.get("resigned_user_id"),
+                    opponent_id: rec.get("opponent_id"),
                 }
             })
             .fetch_all(&mut *self.tx)
@@ -431,10 +615,18 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                 answer: rec.get("answer"),
                 correct_answer: rec.get("correct_answer"),
                 is_correct: rec.get("is_correct"),
+                grade: rec
+                    .get::<Option<i32>, &str>("grade")
+                    .map(AnswerGrade::from_i32),
+                score: rec.get("score"),
                 time: rec.get("time"),
                 timed_out: rec.get("timed_out"),
                 mf_pressed: rec.get("mf_pressed"),
+                gender: rec.get("ptcgender"),
+                case: rec.get("ptccase"),
+                choices: rec.get("choices"),
                 asktimestamp: rec.get("asktimestamp"),
+                answerstarttimestamp: rec.get("answerstarttimestamp"),
                 answeredtimestamp: rec.get("answeredtimestamp"),
             })
             .fetch_one(&mut *self.tx)
@@ -473,10 +665,18 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                 answer: rec.get("answer"),
                 correct_answer: rec.get("correct_answer"),
                 is_correct: rec.get("is_correct"),
+                grade: rec
+                    .get::<Option<i32>, &str>("grade")
+                    .map(AnswerGrade::from_i32),
+                score: rec.get("score"),
                 time: rec.get("time"),
                 timed_out: rec.get("timed_out"),
                 mf_pressed: rec.get("mf_pressed"),
+                gender: rec.get("ptcgender"),
+                case: rec.get("ptccase"),
+                choices: rec.get("choices"),
                 asktimestamp: rec.get("asktimestamp"),
+                answerstarttimestamp: rec.get("answerstarttimestamp"),
                 answeredtimestamp: rec.get("answeredtimestamp"),
             })
             .fetch_all(&mut *self.tx)
@@ -495,7 +695,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         where session_id = $1 \
         LIMIT 1;";
 
-        let res: SessionResult = sqlx::query(query)
+        let res: Option<SessionResult> = sqlx::query(query)
             .bind(session_id)
             .map(|rec: PgRow| SessionResult {
                 session_id: rec.get("session_id"),
@@ -510,13 +710,25 @@ impl HcTrx for HcDbPostgresTrx<'_> {
                 challenger_score: rec.get("challenger_score"),
                 challenged_score: rec.get("challenged_score"),
                 practice_reps_per_verb: rec.get("practice_reps_per_verb"),
+                countdown: rec.get("countdown"),
+                max_time: rec.get("max_time"),
+                win_score: rec.get("win_score"),
+                max_rounds: rec.get("max_rounds"),
+                end_on_verbs_exhausted: rec.get("end_on_verbs_exhausted"),
+                adaptive: rec.get("adaptive"),
+                partial_credit: rec.get("partial_credit"),
+                parsing: rec.get("parsing"),
+                multiple_choice: rec.get("multiple_choice"),
+                nonfinite: rec.get("nonfinite"),
+                status: SessionStatus::from_i32(rec.get("status")),
+                resigned_user_id: rec.get("resigned_user_id"),
                 timestamp: rec.get("timestamp"),
             })
-            .fetch_one(&mut *self.tx)
+            .fetch_optional(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        Ok(res)
+        res.ok_or(HcError::SessionNotFound)
     }
 
     async fn get_used_verbs(&mut self, session_id: sqlx::types::Uuid) -> Result<Vec<i32>, HcError> {
@@ -539,6 +751,7 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         user_id: Option<Uuid>,
         info: &AskQuery,
         timestamp: i64,
+        choices: Option<&str>,
     ) -> Result<Uuid, HcError> {
         let query = "UPDATE sessions SET updated=$1 WHERE session_id=$2;";
         let _res = sqlx::query(query)
@@ -550,19 +763,22 @@ impl HcTrx for HcDbPostgresTrx<'_> {
 
         let uuid = sqlx::types::Uuid::new_v4();
 
-        let query = "INSERT INTO moves VALUES ($1,$2,$3,NULL,$4,$5,$6,$7,$8,$9,NULL,NULL,NULL,NULL,NULL,NULL,$10, NULL);";
+        let query = "INSERT INTO moves (move_id, session_id, ask_user_id, verb_id, person, number, tense, mood, voice, asktimestamp, choices, ptcgender, ptccase) \
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13);";
         let _res = sqlx::query(query)
             .bind(uuid)
             .bind(info.session_id)
             .bind(user_id)
             .bind(info.verb)
-            .bind(info.person)
-            .bind(info.number)
+            .bind(info.move_person())
+            .bind(info.move_number())
             .bind(info.tense)
             .bind(info.mood)
             .bind(info.voice)
             .bind(timestamp)
-            //answer timestamp
+            .bind(choices)
+            .bind(info.move_gender())
+            .bind(info.move_case())
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
@@ -570,36 +786,38 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         Ok(uuid)
     }
 
-    async fn update_answer_move_tx(
+    async fn update_session_status_tx(
         &mut self,
-        info: &AnswerQuery,
-        user_id: Uuid,
-        correct_answer: &str,
-        is_correct: bool,
-        mf_pressed: bool,
+        session_id: sqlx::types::Uuid,
+        status: SessionStatus,
         timestamp: i64,
     ) -> Result<(), HcError> {
-        let m = self.get_last_move_tx(info.session_id).await?;
-
-        let query = "UPDATE sessions SET updated=$1 WHERE session_id=$2;";
+        let query = "UPDATE sessions SET status=$1, updated=$2 WHERE session_id=$3;";
         let _res = sqlx::query(query)
+            .bind(status.to_i32())
             .bind(timestamp)
-            .bind(info.session_id)
+            .bind(session_id)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        let query = "UPDATE moves SET answer_user_id=$1, answer=$2, correct_answer=$3, is_correct=$4, time=$5, mf_pressed=$6, timed_out=$7, answeredtimestamp=$8 WHERE move_id=$9;";
+        Ok(())
+    }
+
+    async fn resign_session_tx(
+        &mut self,
+        session_id: sqlx::types::Uuid,
+        user_id: sqlx::types::Uuid,
+        status: SessionStatus,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query =
+            "UPDATE sessions SET resigned_user_id=$1, status=$2, updated=$3 WHERE session_id=$4;";
         let _res = sqlx::query(query)
             .bind(user_id)
-            .bind(info.answer.clone())
-            .bind(correct_answer)
-            .bind(is_correct)
-            .bind(info.time.clone())
-            .bind(mf_pressed)
-            .bind(info.timed_out)
+            .bind(status.to_i32())
             .bind(timestamp)
-            .bind(m.move_id)
+            .bind(session_id)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
@@ -607,157 +825,901 @@ impl HcTrx for HcDbPostgresTrx<'_> {
         Ok(())
     }
 
-    async fn create_user(
+    async fn insert_abandoned_session_tx(
         &mut self,
-        google_oauth_sub: Option<&str>,
-        apple_oauth_sub: Option<&str>,
-        username: Option<&str>,
-        password: Secret<String>,
-        email: &str,
-        first_name: &str,
-        last_name: &str,
+        session_id: sqlx::types::Uuid,
+        user_id: sqlx::types::Uuid,
         timestamp: i64,
-    ) -> Result<Uuid, HcError> {
-        let uuid = sqlx::types::Uuid::new_v4();
-        let query = "INSERT INTO users VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 0, $9, $10);";
+    ) -> Result<(), HcError> {
+        let query =
+            "INSERT INTO abandoned_sessions (session_id, user_id, timestamp) VALUES ($1, $2, $3);";
         let _res = sqlx::query(query)
-            .bind(uuid)
-            .bind(google_oauth_sub)
-            .bind(apple_oauth_sub)
-            .bind(username)
-            .bind(password.expose_secret())
-            .bind(email)
-            .bind(first_name)
-            .bind(last_name)
-            .bind(timestamp)
+            .bind(session_id)
+            .bind(user_id)
             .bind(timestamp)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        Ok(uuid)
+        Ok(())
     }
 
-    async fn get_credentials(
+    async fn get_abandoned_sessions_tx(
         &mut self,
-        username: &str,
-    ) -> Result<Option<(uuid::Uuid, Secret<String>)>, HcError> {
-        let row = sqlx::query(
-            r#"
-            SELECT user_id, password
-            FROM users
-            WHERE user_name = $1
-            "#,
-        )
-        .bind(username)
-        .map(|row: PgRow| (row.get("user_id"), Secret::new(row.get("password"))))
-        .fetch_optional(&mut *self.tx)
-        .await
-        .map_err(map_sqlx_error)?;
+        user_id: sqlx::types::Uuid,
+    ) -> Result<Vec<sqlx::types::Uuid>, HcError> {
+        let query = "SELECT session_id FROM abandoned_sessions WHERE user_id = $1;";
+        let res: Vec<sqlx::types::Uuid> = sqlx::query(query)
+            .bind(user_id)
+            .map(|rec: PgRow| rec.get("session_id"))
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
 
-        Ok(row)
+        Ok(res)
     }
 
-    async fn get_oauth_user(
+    async fn expire_invitations_tx(
         &mut self,
-        oauth_iss: &str,
-        oauth_sub: &str,
-    ) -> Result<Option<(uuid::Uuid, Option<String>)>, HcError> {
-        // https://accounts.google.com
-        let query = format!(
-            r#"
-            SELECT user_id, user_name
-            FROM users
-            WHERE {} = $1;"#,
-            if oauth_iss == "https://appleid.apple.com" {
-                "apple_oauth_sub"
-            } else {
-                "google_oauth_sub"
-            }
-        );
+        user_id: sqlx::types::Uuid,
+        expires_before: i64,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE sessions SET status=$1, updated=$2 \
+        WHERE status=$3 AND timestamp < $4 AND (challenger_user_id=$5 OR challenged_user_id=$6);";
+        let _res = sqlx::query(query)
+            .bind(SessionStatus::Expired.to_i32())
+            .bind(timestamp)
+            .bind(SessionStatus::Pending.to_i32())
+            .bind(expires_before)
+            .bind(user_id)
+            .bind(user_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
 
-        let row = sqlx::query(&query)
-            .bind(oauth_sub)
-            .map(|row: PgRow| (row.get("user_id"), row.get("user_name")))
+        Ok(())
+    }
+
+    async fn get_user_name_tx(&mut self, user_id: Uuid) -> Result<Option<String>, HcError> {
+        let query = "SELECT user_name FROM users WHERE user_id = $1;";
+        let res: Option<Option<String>> = sqlx::query(query)
+            .bind(user_id)
+            .map(|rec: PgRow| rec.get("user_name"))
             .fetch_optional(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        Ok(row)
+        Ok(res.flatten())
     }
 
-    async fn create_db(&mut self) -> Result<(), HcError> {
-        let query = r#"CREATE TABLE IF NOT EXISTS users (
-    user_id UUID PRIMARY KEY NOT NULL,
-    google_oauth_sub TEXT,
-    apple_oauth_sub TEXT,
-    user_name TEXT,
-    password TEXT,
-    email TEXT,
-    first_name TEXT,
-    last_name TEXT,
-    user_type BIGINT NOT NULL DEFAULT 0,
-    timestamp BIGINT NOT NULL DEFAULT 0,
-    created BIGINT NOT NULL DEFAULT 0,
-    UNIQUE(user_name),
-    UNIQUE(google_oauth_sub),
-    UNIQUE(apple_oauth_sub)
-    );"#;
-
-        let _res = sqlx::query(query)
-            .execute(&mut *self.tx)
+    async fn get_user_type_tx(&mut self, user_id: Uuid) -> Result<Option<i64>, HcError> {
+        let query = "SELECT user_type FROM users WHERE user_id = $1;";
+        let res: Option<i64> = sqlx::query(query)
+            .bind(user_id)
+            .map(|rec: PgRow| rec.get("user_type"))
+            .fetch_optional(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        let query = r#"CREATE TABLE IF NOT EXISTS sessions (
-    session_id UUID PRIMARY KEY NOT NULL,
-    challenger_user_id UUID NOT NULL,
-    challenged_user_id UUID DEFAULT NULL,
-    current_move UUID DEFAULT NULL,
-    name TEXT DEFAULT NULL,
-    highest_unit SMALLINT,
-    custom_verbs TEXT,
-    custom_params TEXT,
-    max_changes SMALLINT,
-    challenger_score INT,
-    challenged_score INT,
-    practice_reps_per_verb SMALLINT,
-    countdown INT,
-    max_time INT,
-    timestamp BIGINT NOT NULL DEFAULT 0,
-    updated BIGINT NOT NULL DEFAULT 0,
-    status INT NOT NULL DEFAULT 1,
-    FOREIGN KEY (challenger_user_id) REFERENCES users(user_id),
-    FOREIGN KEY (challenged_user_id) REFERENCES users(user_id)
-    );"#;
+        Ok(res)
+    }
+
+    async fn set_user_type_tx(&mut self, user_id: Uuid, user_type: i64) -> Result<(), HcError> {
+        let query = "UPDATE users SET user_type = $1 WHERE user_id = $2;";
         let _res = sqlx::query(query)
+            .bind(user_type)
+            .bind(user_id)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
-        let query = r#"CREATE TABLE IF NOT EXISTS moves (
-    move_id UUID PRIMARY KEY NOT NULL,
-    session_id UUID NOT NULL,
-    ask_user_id UUID,
-    answer_user_id UUID,
-    verb_id INT,
-    person SMALLINT,
-    number SMALLINT,
-    tense SMALLINT,
-    mood SMALLINT,
-    voice SMALLINT,
-    answer VARCHAR(1024),
-    correct_answer VARCHAR(1024),
-    is_correct BOOL,
-    time VARCHAR(255),
-    timed_out BOOL,
-    mf_pressed BOOL,
-    asktimestamp BIGINT NOT NULL DEFAULT 0,
-    answeredtimestamp BIGINT,
-    FOREIGN KEY (ask_user_id) REFERENCES users(user_id),
-    FOREIGN KEY (answer_user_id) REFERENCES users(user_id),
-    FOREIGN KEY (session_id) REFERENCES sessions(session_id)
-    );"#;
+        Ok(())
+    }
+
+    async fn get_move_tx(&mut self, move_id: Uuid) -> Result<Option<MoveResult>, HcError> {
+        let query = "SELECT * FROM moves WHERE move_id = $1;";
+        let res: Option<MoveResult> = sqlx::query(query)
+            .bind(move_id)
+            .map(|rec: PgRow| MoveResult {
+                move_id: rec.get("move_id"),
+                session_id: rec.get("session_id"),
+                ask_user_id: rec.get("ask_user_id"),
+                answer_user_id: rec.get("answer_user_id"),
+                verb_id: rec.get("verb_id"),
+                person: rec.get("person"),
+                number: rec.get("number"),
+                tense: rec.get("tense"),
+                mood: rec.get("mood"),
+                voice: rec.get("voice"),
+                answer: rec.get("answer"),
+                correct_answer: rec.get("correct_answer"),
+                is_correct: rec.get("is_correct"),
+                grade: rec
+                    .get::<Option<i32>, &str>("grade")
+                    .map(AnswerGrade::from_i32),
+                score: rec.get("score"),
+                time: rec.get("time"),
+                timed_out: rec.get("timed_out"),
+                mf_pressed: rec.get("mf_pressed"),
+                gender: rec.get("ptcgender"),
+                case: rec.get("ptccase"),
+                choices: rec.get("choices"),
+                asktimestamp: rec.get("asktimestamp"),
+                answerstarttimestamp: rec.get("answerstarttimestamp"),
+                answeredtimestamp: rec.get("answeredtimestamp"),
+            })
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn update_move_grade_tx(
+        &mut self,
+        move_id: Uuid,
+        is_correct: bool,
+        grade: AnswerGrade,
+        score: f64,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE moves SET is_correct = $1, grade = $2, score = $3 WHERE move_id = $4;";
+        let _res = sqlx::query(query)
+            .bind(is_correct)
+            .bind(grade.to_i32())
+            .bind(score)
+            .bind(move_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn start_answer_move_tx(&mut self, move_id: Uuid, timestamp: i64) -> Result<(), HcError> {
+        //only the first start counts, so opening the move again cannot restart its countdown
+        let query = "UPDATE moves SET answerstarttimestamp = $1 \
+            WHERE move_id = $2 AND answerstarttimestamp IS NULL;";
+        let _res = sqlx::query(query)
+            .bind(timestamp)
+            .bind(move_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn update_move_mf_pressed_tx(&mut self, move_id: Uuid) -> Result<(), HcError> {
+        let query = "UPDATE moves SET mf_pressed = $1 WHERE move_id = $2;";
+        let _res = sqlx::query(query)
+            .bind(true)
+            .bind(move_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn insert_dispute_tx(&mut self, dispute: &DisputeResult) -> Result<(), HcError> {
+        let query =
+            "INSERT INTO disputes (dispute_id, move_id, session_id, user_id, reason, status, \
+        original_is_correct, resolved_by, created, resolved) \
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);";
+        let _res = sqlx::query(query)
+            .bind(dispute.dispute_id)
+            .bind(dispute.move_id)
+            .bind(dispute.session_id)
+            .bind(dispute.user_id)
+            .bind(&dispute.reason)
+            .bind(dispute.status.to_i32())
+            .bind(dispute.original_is_correct)
+            .bind(dispute.resolved_by)
+            .bind(dispute.created)
+            .bind(dispute.resolved)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_dispute_tx(&mut self, dispute_id: Uuid) -> Result<Option<DisputeResult>, HcError> {
+        let query = "SELECT * FROM disputes WHERE dispute_id = $1;";
+        let res: Option<DisputeResult> = sqlx::query(query)
+            .bind(dispute_id)
+            .map(|rec: PgRow| DisputeResult {
+                dispute_id: rec.get("dispute_id"),
+                move_id: rec.get("move_id"),
+                session_id: rec.get("session_id"),
+                user_id: rec.get("user_id"),
+                reason: rec.get("reason"),
+                status: DisputeStatus::from_i32(rec.get("status")),
+                original_is_correct: rec.get("original_is_correct"),
+                resolved_by: rec.get("resolved_by"),
+                created: rec.get("created"),
+                resolved: rec.get("resolved"),
+            })
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn get_disputes_tx(&mut self, session_id: Uuid) -> Result<Vec<DisputeResult>, HcError> {
+        let query = "SELECT * FROM disputes WHERE session_id = $1 ORDER BY created, dispute_id;";
+        let res: Vec<DisputeResult> = sqlx::query(query)
+            .bind(session_id)
+            .map(|rec: PgRow| DisputeResult {
+                dispute_id: rec.get("dispute_id"),
+                move_id: rec.get("move_id"),
+                session_id: rec.get("session_id"),
+                user_id: rec.get("user_id"),
+                reason: rec.get("reason"),
+                status: DisputeStatus::from_i32(rec.get("status")),
+                original_is_correct: rec.get("original_is_correct"),
+                resolved_by: rec.get("resolved_by"),
+                created: rec.get("created"),
+                resolved: rec.get("resolved"),
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn resolve_dispute_tx(
+        &mut self,
+        dispute_id: Uuid,
+        status: DisputeStatus,
+        resolved_by: Uuid,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE disputes SET status = $1, resolved_by = $2, resolved = $3 WHERE dispute_id = $4;";
+        let _res = sqlx::query(query)
+            .bind(status.to_i32())
+            .bind(resolved_by)
+            .bind(timestamp)
+            .bind(dispute_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn insert_chat_message_tx(&mut self, chat: &ChatResult) -> Result<(), HcError> {
+        let query =
+            "INSERT INTO chat_messages (message_id, session_id, user_id, message, created, \
+        removed_by, removed) \
+        VALUES ($1, $2, $3, $4, $5, $6, $7);";
+        let _res = sqlx::query(query)
+            .bind(chat.message_id)
+            .bind(chat.session_id)
+            .bind(chat.user_id)
+            .bind(&chat.message)
+            .bind(chat.created)
+            .bind(chat.removed_by)
+            .bind(chat.removed)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_chat_message_tx(
+        &mut self,
+        message_id: Uuid,
+    ) -> Result<Option<ChatResult>, HcError> {
+        let query = "SELECT a.*, b.user_name FROM chat_messages a \
+        LEFT JOIN users b ON a.user_id = b.user_id \
+        WHERE message_id = $1;";
+        let res: Option<ChatResult> = sqlx::query(query)
+            .bind(message_id)
+            .map(|rec: PgRow| ChatResult {
+                message_id: rec.get("message_id"),
+                session_id: rec.get("session_id"),
+                user_id: rec.get("user_id"),
+                user_name: rec.get("user_name"),
+                message: rec.get("message"),
+                created: rec.get("created"),
+                removed_by: rec.get("removed_by"),
+                removed: rec.get("removed"),
+            })
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn get_chat_messages_tx(&mut self, session_id: Uuid) -> Result<Vec<ChatResult>, HcError> {
+        let query = "SELECT a.*, b.user_name FROM chat_messages a \
+        LEFT JOIN users b ON a.user_id = b.user_id \
+        WHERE session_id = $1 AND removed IS NULL \
+        ORDER BY created, message_id;";
+        let res: Vec<ChatResult> = sqlx::query(query)
+            .bind(session_id)
+            .map(|rec: PgRow| ChatResult {
+                message_id: rec.get("message_id"),
+                session_id: rec.get("session_id"),
+                user_id: rec.get("user_id"),
+                user_name: rec.get("user_name"),
+                message: rec.get("message"),
+                created: rec.get("created"),
+                removed_by: rec.get("removed_by"),
+                removed: rec.get("removed"),
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn remove_chat_message_tx(
+        &mut self,
+        message_id: Uuid,
+        removed_by: Uuid,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE chat_messages SET removed_by = $1, removed = $2 WHERE message_id = $3;";
+        let _res = sqlx::query(query)
+            .bind(removed_by)
+            .bind(timestamp)
+            .bind(message_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_share_token_tx(&mut self, session_id: Uuid) -> Result<Option<String>, HcError> {
+        let query = "SELECT share_token FROM sessions WHERE session_id = $1;";
+        let res: Option<Option<String>> = sqlx::query(query)
+            .bind(session_id)
+            .map(|rec: PgRow| rec.get("share_token"))
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res.flatten())
+    }
+
+    async fn set_share_token_tx(
+        &mut self,
+        session_id: Uuid,
+        share_token: &str,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE sessions SET share_token = $1 WHERE session_id = $2;";
+        let _res = sqlx::query(query)
+            .bind(share_token)
+            .bind(session_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_rating_tx(&mut self, user_id: Uuid) -> Result<Option<i32>, HcError> {
+        let query = "SELECT rating FROM ratings WHERE user_id = $1;";
+        let res: Option<i32> = sqlx::query(query)
+            .bind(user_id)
+            .map(|rec: PgRow| rec.get("rating"))
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn update_rating_tx(
+        &mut self,
+        user_id: Uuid,
+        rating: i32,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query = "INSERT INTO ratings (user_id, rating, rated_games, updated) VALUES ($1, $2, 1, $3) \
+        ON CONFLICT (user_id) DO UPDATE SET rating=excluded.rating, rated_games=ratings.rated_games + 1, updated=excluded.updated;";
+        let _res = sqlx::query(query)
+            .bind(user_id)
+            .bind(rating)
+            .bind(timestamp)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn get_leaderboard_tx(
+        &mut self,
+        class_code: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<LeaderboardRow>, HcError> {
+        let query = "SELECT b.user_name, a.rating, a.rated_games \
+        FROM ratings a \
+        INNER JOIN users b ON a.user_id = b.user_id \
+        WHERE ($1 IS NULL OR b.class_code = $2) \
+        ORDER BY a.rating DESC, b.user_name \
+        LIMIT $3;";
+
+        let res: Vec<(Option<String>, i32, i32)> = sqlx::query(query)
+            .bind(class_code)
+            .bind(class_code)
+            .bind(limit as i64)
+            .map(|rec: PgRow| {
+                (
+                    rec.get("user_name"),
+                    rec.get("rating"),
+                    rec.get("rated_games"),
+                )
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res
+            .into_iter()
+            .enumerate()
+            .map(|(i, (user_name, rating, rated_games))| LeaderboardRow {
+                rank: i as u32 + 1,
+                user_name: user_name.unwrap_or_default(),
+                rating,
+                rated_games,
+            })
+            .collect())
+    }
+
+    async fn get_head_to_head_tx(
+        &mut self,
+        user_id: Uuid,
+        opponent_id: Uuid,
+    ) -> Result<Vec<SessionResult>, HcError> {
+        let query = "SELECT * \
+        FROM sessions \
+        WHERE ((challenger_user_id = $1 AND challenged_user_id = $2) \
+        OR (challenger_user_id = $3 AND challenged_user_id = $4)) \
+        AND status IN ($5, $6) \
+        ORDER BY timestamp DESC;";
+
+        let res: Vec<SessionResult> = sqlx::query(query)
+            .bind(user_id)
+            .bind(opponent_id)
+            .bind(opponent_id)
+            .bind(user_id)
+            .bind(SessionStatus::Finished.to_i32())
+            .bind(SessionStatus::Abandoned.to_i32())
+            .map(|rec: PgRow| SessionResult {
+                session_id: rec.get("session_id"),
+                challenger_user_id: rec.get("challenger_user_id"),
+                challenged_user_id: rec.get("challenged_user_id"),
+                current_move: rec.get("current_move"),
+                name: rec.get("name"),
+                highest_unit: rec.get("highest_unit"),
+                custom_verbs: rec.get("custom_verbs"),
+                custom_params: rec.get("custom_params"),
+                max_changes: rec.get("max_changes"),
+                challenger_score: rec.get("challenger_score"),
+                challenged_score: rec.get("challenged_score"),
+                practice_reps_per_verb: rec.get("practice_reps_per_verb"),
+                countdown: rec.get("countdown"),
+                max_time: rec.get("max_time"),
+                win_score: rec.get("win_score"),
+                max_rounds: rec.get("max_rounds"),
+                end_on_verbs_exhausted: rec.get("end_on_verbs_exhausted"),
+                adaptive: rec.get("adaptive"),
+                partial_credit: rec.get("partial_credit"),
+                parsing: rec.get("parsing"),
+                multiple_choice: rec.get("multiple_choice"),
+                nonfinite: rec.get("nonfinite"),
+                status: SessionStatus::from_i32(rec.get("status")),
+                resigned_user_id: rec.get("resigned_user_id"),
+                timestamp: rec.get("timestamp"),
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn set_class_code_tx(
+        &mut self,
+        user_id: Uuid,
+        class_code: Option<&str>,
+    ) -> Result<(), HcError> {
+        let query = "UPDATE users SET class_code = $1 WHERE user_id = $2;";
+        let _res = sqlx::query(query)
+            .bind(class_code)
+            .bind(user_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    //a class exists once an instructor has chosen its code
+    async fn class_exists_tx(&mut self, class_code: &str) -> Result<bool, HcError> {
+        let query = "SELECT COUNT(*) AS n FROM users WHERE class_code = $1 AND user_type >= $2;";
+        let n: i64 = sqlx::query(query)
+            .bind(class_code)
+            .bind(USER_TYPE_INSTRUCTOR)
+            .map(|rec: PgRow| rec.get("n"))
+            .fetch_one(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(n > 0)
+    }
+
+    async fn get_class_code_tx(&mut self, user_id: Uuid) -> Result<Option<String>, HcError> {
+        let query = "SELECT class_code FROM users WHERE user_id = $1;";
+        let res: Option<Option<String>> = sqlx::query(query)
+            .bind(user_id)
+            .map(|rec: PgRow| rec.get("class_code"))
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res.flatten())
+    }
+
+    async fn get_user_answers_tx(
+        &mut self,
+        user_id: Uuid,
+        from: Option<i64>,
+        to: Option<i64>,
+        practice: Option<bool>,
+    ) -> Result<Vec<MoveResult>, HcError> {
+        let query = "SELECT m.* \
+        FROM moves m \
+        INNER JOIN sessions s ON m.session_id = s.session_id \
+        WHERE m.answer_user_id = $1 AND m.is_correct IS NOT NULL \
+        AND ($2 IS NULL OR m.answeredtimestamp >= $3) \
+        AND ($4 IS NULL OR m.answeredtimestamp <= $5) \
+        AND ($6 IS NULL OR (s.challenged_user_id IS NULL) = $7) \
+        ORDER BY m.answeredtimestamp DESC;";
+
+        let res: Vec<MoveResult> = sqlx::query(query)
+            .bind(user_id)
+            .bind(from)
+            .bind(from)
+            .bind(to)
+            .bind(to)
+            .bind(practice)
+            .bind(practice)
+            .map(|rec: PgRow| MoveResult {
+                move_id: rec.get("move_id"),
+                session_id: rec.get("session_id"),
+                ask_user_id: rec.get("ask_user_id"),
+                answer_user_id: rec.get("answer_user_id"),
+                verb_id: rec.get("verb_id"),
+                person: rec.get("person"),
+                number: rec.get("number"),
+                tense: rec.get("tense"),
+                mood: rec.get("mood"),
+                voice: rec.get("voice"),
+                answer: rec.get("answer"),
+                correct_answer: rec.get("correct_answer"),
+                is_correct: rec.get("is_correct"),
+                grade: rec
+                    .get::<Option<i32>, &str>("grade")
+                    .map(AnswerGrade::from_i32),
+                score: rec.get("score"),
+                time: rec.get("time"),
+                timed_out: rec.get("timed_out"),
+                mf_pressed: rec.get("mf_pressed"),
+                gender: rec.get("ptcgender"),
+                case: rec.get("ptccase"),
+                choices: rec.get("choices"),
+                asktimestamp: rec.get("asktimestamp"),
+                answerstarttimestamp: rec.get("answerstarttimestamp"),
+                answeredtimestamp: rec.get("answeredtimestamp"),
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn get_mastery_tx(
+        &mut self,
+        user_id: Uuid,
+        verb_id: Option<i32>,
+    ) -> Result<Vec<MasteryResult>, HcError> {
+        let query = "SELECT * \
+        FROM mastery \
+        WHERE user_id = $1 AND ($2 IS NULL OR verb_id = $3);";
+
+        let res: Vec<MasteryResult> = sqlx::query(query)
+            .bind(user_id)
+            .bind(verb_id)
+            .bind(verb_id)
+            .map(|rec: PgRow| MasteryResult {
+                verb_id: rec.get("verb_id"),
+                person: rec.get("person"),
+                number: rec.get("number"),
+                tense: rec.get("tense"),
+                voice: rec.get("voice"),
+                mood: rec.get("mood"),
+                leitner_box: rec.get("leitner_box"),
+                correct_count: rec.get("correct_count"),
+                incorrect_count: rec.get("incorrect_count"),
+                due: rec.get("due"),
+            })
+            .fetch_all(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(res)
+    }
+
+    async fn update_mastery_tx(
+        &mut self,
+        user_id: Uuid,
+        mastery: &MasteryResult,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let query = r#"INSERT INTO mastery (
+            user_id,
+            verb_id,
+            person,
+            number,
+            tense,
+            voice,
+            mood,
+            leitner_box,
+            correct_count,
+            incorrect_count,
+            due,
+            updated) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+            ON CONFLICT (user_id, verb_id, person, number, tense, voice, mood) DO UPDATE SET
+            leitner_box=excluded.leitner_box,
+            correct_count=excluded.correct_count,
+            incorrect_count=excluded.incorrect_count,
+            due=excluded.due,
+            updated=excluded.updated;"#;
+        let _res = sqlx::query(query)
+            .bind(user_id)
+            .bind(mastery.verb_id)
+            .bind(mastery.person)
+            .bind(mastery.number)
+            .bind(mastery.tense)
+            .bind(mastery.voice)
+            .bind(mastery.mood)
+            .bind(mastery.leitner_box)
+            .bind(mastery.correct_count)
+            .bind(mastery.incorrect_count)
+            .bind(mastery.due)
+            .bind(timestamp)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn update_answer_move_tx(
+        &mut self,
+        info: &AnswerQuery,
+        user_id: Uuid,
+        correct_answer: &str,
+        is_correct: bool,
+        grade: AnswerGrade,
+        score: f64,
+        mf_pressed: bool,
+        timed_out: bool,
+        timestamp: i64,
+    ) -> Result<(), HcError> {
+        let m = self.get_last_move_tx(info.session_id).await?;
+
+        let query = "UPDATE sessions SET updated=$1 WHERE session_id=$2;";
+        let _res = sqlx::query(query)
+            .bind(timestamp)
+            .bind(info.session_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = "UPDATE moves SET answer_user_id=$1, answer=$2, correct_answer=$3, is_correct=$4, grade=$5, score=$6, time=$7, mf_pressed=$8, timed_out=$9, answeredtimestamp=$10 WHERE move_id=$11;";
+        let _res = sqlx::query(query)
+            .bind(user_id)
+            .bind(info.answer.clone())
+            .bind(correct_answer)
+            .bind(is_correct)
+            .bind(grade.to_i32())
+            .bind(score)
+            .bind(info.time.clone())
+            .bind(mf_pressed)
+            .bind(timed_out)
+            .bind(timestamp)
+            .bind(m.move_id)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(())
+    }
+
+    async fn create_user(
+        &mut self,
+        google_oauth_sub: Option<&str>,
+        apple_oauth_sub: Option<&str>,
+        username: Option<&str>,
+        password: Secret<String>,
+        email: &str,
+        first_name: &str,
+        last_name: &str,
+        timestamp: i64,
+    ) -> Result<Uuid, HcError> {
+        let uuid = sqlx::types::Uuid::new_v4();
+        let query = "INSERT INTO users (user_id, google_oauth_sub, apple_oauth_sub, user_name, password, email, first_name, last_name, user_type, timestamp, created) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 0, $9, $10);";
+        let _res = sqlx::query(query)
+            .bind(uuid)
+            .bind(google_oauth_sub)
+            .bind(apple_oauth_sub)
+            .bind(username)
+            .bind(password.expose_secret())
+            .bind(email)
+            .bind(first_name)
+            .bind(last_name)
+            .bind(timestamp)
+            .bind(timestamp)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(uuid)
+    }
+
+    async fn get_credentials(
+        &mut self,
+        username: &str,
+    ) -> Result<Option<(uuid::Uuid, Secret<String>)>, HcError> {
+        let row = sqlx::query(
+            r#"
+            SELECT user_id, password
+            FROM users
+            WHERE user_name = $1
+            "#,
+        )
+        .bind(username)
+        .map(|row: PgRow| (row.get("user_id"), Secret::new(row.get("password"))))
+        .fetch_optional(&mut *self.tx)
+        .await
+        .map_err(map_sqlx_error)?;
+
+        Ok(row)
+    }
+
+    async fn get_oauth_user(
+        &mut self,
+        oauth_iss: &str,
+        oauth_sub: &str,
+    ) -> Result<Option<(uuid::Uuid, Option<String>)>, HcError> {
+        // https://accounts.google.com
+        let query = format!(
+            r#"
+            SELECT user_id, user_name
+            FROM users
+            WHERE {} = $1;"#,
+            if oauth_iss == "https://appleid.apple.com" {
+                "apple_oauth_sub"
+            } else {
+                "google_oauth_sub"
+            }
+        );
+
+        let row = sqlx::query(&query)
+            .bind(oauth_sub)
+            .map(|row: PgRow| (row.get("user_id"), row.get("user_name")))
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        Ok(row)
+    }
+
+    async fn create_db(&mut self) -> Result<(), HcError> {
+        let query = r#"CREATE TABLE IF NOT EXISTS users (
+    user_id UUID PRIMARY KEY NOT NULL,
+    google_oauth_sub TEXT,
+    apple_oauth_sub TEXT,
+    user_name TEXT,
+    password TEXT,
+    email TEXT,
+    first_name TEXT,
+    last_name TEXT,
+    user_type BIGINT NOT NULL DEFAULT 0,
+    timestamp BIGINT NOT NULL DEFAULT 0,
+    created BIGINT NOT NULL DEFAULT 0,
+    class_code TEXT DEFAULT NULL,
+    UNIQUE(user_name),
+    UNIQUE(google_oauth_sub),
+    UNIQUE(apple_oauth_sub)
+    );"#;
+
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS sessions (
+    session_id UUID PRIMARY KEY NOT NULL,
+    challenger_user_id UUID NOT NULL,
+    challenged_user_id UUID DEFAULT NULL,
+    current_move UUID DEFAULT NULL,
+    name TEXT DEFAULT NULL,
+    highest_unit SMALLINT,
+    custom_verbs TEXT,
+    custom_params TEXT,
+    max_changes SMALLINT,
+    challenger_score DOUBLE PRECISION,
+    challenged_score DOUBLE PRECISION,
+    practice_reps_per_verb SMALLINT,
+    countdown INT,
+    max_time INT,
+    win_score INT,
+    max_rounds INT,
+    end_on_verbs_exhausted INT,
+    adaptive INT,
+    partial_credit INT,
+    parsing INT,
+    multiple_choice INT,
+    nonfinite INT,
+    timestamp BIGINT NOT NULL DEFAULT 0,
+    updated BIGINT NOT NULL DEFAULT 0,
+    status INT NOT NULL DEFAULT 1,
+    resigned_user_id UUID DEFAULT NULL,
+    share_token TEXT DEFAULT NULL,
+    FOREIGN KEY (challenger_user_id) REFERENCES users(user_id),
+    FOREIGN KEY (challenged_user_id) REFERENCES users(user_id),
+    FOREIGN KEY (resigned_user_id) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS moves (
+    move_id UUID PRIMARY KEY NOT NULL,
+    session_id UUID NOT NULL,
+    ask_user_id UUID,
+    answer_user_id UUID,
+    verb_id INT,
+    person SMALLINT,
+    number SMALLINT,
+    tense SMALLINT,
+    mood SMALLINT,
+    voice SMALLINT,
+    answer VARCHAR(1024),
+    correct_answer VARCHAR(1024),
+    is_correct BOOL,
+    grade INT,
+    score DOUBLE PRECISION,
+    time VARCHAR(255),
+    timed_out BOOL,
+    mf_pressed BOOL,
+    asktimestamp BIGINT NOT NULL DEFAULT 0,
+    answerstarttimestamp BIGINT,
+    answeredtimestamp BIGINT,
+    choices VARCHAR(1024),
+    ptcgender SMALLINT,
+    ptccase SMALLINT,
+    FOREIGN KEY (ask_user_id) REFERENCES users(user_id),
+    FOREIGN KEY (answer_user_id) REFERENCES users(user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(session_id)
+    );"#;
         let _res = sqlx::query(query)
             .execute(&mut *self.tx)
             .await
@@ -769,6 +1731,97 @@ impl HcTrx for HcDbPostgresTrx<'_> {
             .await
             .map_err(map_sqlx_error)?;
 
+        let query = r#"CREATE TABLE IF NOT EXISTS disputes (
+    dispute_id UUID PRIMARY KEY NOT NULL,
+    move_id UUID NOT NULL,
+    session_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    reason TEXT,
+    status INT NOT NULL DEFAULT 0,
+    original_is_correct BOOL NOT NULL,
+    resolved_by UUID,
+    created BIGINT NOT NULL DEFAULT 0,
+    resolved BIGINT,
+    FOREIGN KEY (move_id) REFERENCES moves(move_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(session_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id),
+    FOREIGN KEY (resolved_by) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        //a player who abandons a game no longer sees it; their opponent still does
+        let query = r#"CREATE TABLE IF NOT EXISTS abandoned_sessions (
+    session_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    timestamp BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (session_id, user_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(session_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS chat_messages (
+    message_id UUID PRIMARY KEY NOT NULL,
+    session_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    message TEXT NOT NULL,
+    created BIGINT NOT NULL DEFAULT 0,
+    removed_by UUID,
+    removed BIGINT,
+    FOREIGN KEY (session_id) REFERENCES sessions(session_id),
+    FOREIGN KEY (user_id) REFERENCES users(user_id),
+    FOREIGN KEY (removed_by) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = "CREATE INDEX IF NOT EXISTS chat_session_id_idx ON chat_messages (session_id);";
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS ratings (
+    user_id UUID PRIMARY KEY NOT NULL,
+    rating INT NOT NULL DEFAULT 1200,
+    rated_games INT NOT NULL DEFAULT 0,
+    updated BIGINT NOT NULL DEFAULT 0,
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS mastery (
+    user_id UUID NOT NULL,
+    verb_id INT NOT NULL,
+    person SMALLINT NOT NULL,
+    number SMALLINT NOT NULL,
+    tense SMALLINT NOT NULL,
+    voice SMALLINT NOT NULL,
+    mood SMALLINT NOT NULL,
+    leitner_box INT NOT NULL DEFAULT 0,
+    correct_count INT NOT NULL DEFAULT 0,
+    incorrect_count INT NOT NULL DEFAULT 0,
+    due BIGINT NOT NULL DEFAULT 0,
+    updated BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, verb_id, person, number, tense, voice, mood),
+    FOREIGN KEY (user_id) REFERENCES users(user_id)
+    );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
         let query = r#"CREATE TABLE IF NOT EXISTS greeksynopsisresults (
             id UUID PRIMARY KEY NOT NULL,
             user_id UUID,
@@ -852,12 +1905,92 @@ impl HcTrx for HcDbPostgresTrx<'_> {
             f60 TEXT NOT NULL, a60 TEXT NOT NULL, c60 BOOLEAN NOT NULL,
             f61 TEXT NOT NULL, a61 TEXT NOT NULL, c61 BOOLEAN NOT NULL,
             f62 TEXT NOT NULL, a62 TEXT NOT NULL, c62 BOOLEAN NOT NULL,
+            grades TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(user_id) );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS greekparadigmresults (
+            id UUID PRIMARY KEY NOT NULL,
+            user_id UUID,
+            updated timestamp default (now() at time zone 'utc'),
+            sname TEXT NOT NULL,
+            advisor TEXT NOT NULL,
+            sgiday INTEGER NOT NULL,
+            selectedverb TEXT NOT NULL,
+            pp TEXT NOT NULL,
+            pp_correct TEXT NOT NULL,
+            pp_is_correct TEXT NOT NULL,
+            verbtense INTEGER NOT NULL,
+            verbvoice INTEGER NOT NULL,
+            verbmood INTEGER NOT NULL,
+            ip TEXT NOT NULL,
+            ua TEXT NOT NULL,
+            status INTEGER NOT NULL,
+            score TEXT NOT NULL,
+            grades TEXT NOT NULL,
+            f0 TEXT NOT NULL, a0 TEXT NOT NULL, c0 BOOLEAN NOT NULL,
+            f1 TEXT NOT NULL, a1 TEXT NOT NULL, c1 BOOLEAN NOT NULL,
+            f2 TEXT NOT NULL, a2 TEXT NOT NULL, c2 BOOLEAN NOT NULL,
+            f3 TEXT NOT NULL, a3 TEXT NOT NULL, c3 BOOLEAN NOT NULL,
+            f4 TEXT NOT NULL, a4 TEXT NOT NULL, c4 BOOLEAN NOT NULL,
+            f5 TEXT NOT NULL, a5 TEXT NOT NULL, c5 BOOLEAN NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(user_id) );"#;
+        let _res = sqlx::query(query)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(map_sqlx_error)?;
+
+        let query = r#"CREATE TABLE IF NOT EXISTS greekppresults (
+            id UUID PRIMARY KEY NOT NULL,
+            user_id UUID,
+            updated timestamp default (now() at time zone 'utc'),
+            verb_id INTEGER NOT NULL,
+            unit INTEGER NOT NULL,
+            pp TEXT NOT NULL,
+            pp_correct TEXT NOT NULL,
+            pp_is_correct TEXT NOT NULL,
+            score INTEGER NOT NULL,
             FOREIGN KEY (user_id) REFERENCES users(user_id) );"#;
         let _res = sqlx::query(query)
             .execute(&mut *self.tx)
             .await
             .map_err(map_sqlx_error)?;
 
+        //tables created by an earlier version
+        self.add_column("sessions", "win_score", "INT").await?;
+        self.add_column("sessions", "max_rounds", "INT").await?;
+        self.add_column("sessions", "end_on_verbs_exhausted", "INT")
+            .await?;
+        self.add_column("sessions", "resigned_user_id", "UUID DEFAULT NULL")
+            .await?;
+        self.add_column("sessions", "adaptive", "INT").await?;
+        self.add_column("users", "class_code", "TEXT DEFAULT NULL")
+            .await?;
+        self.add_column("sessions", "partial_credit", "INT").await?;
+        self.add_column("moves", "grade", "INT").await?;
+        self.add_column("moves", "score", "DOUBLE PRECISION")
+            .await?;
+        self.add_column("greeksynopsisresults", "grades", "TEXT")
+            .await?;
+        self.add_column("sessions", "parsing", "INT").await?;
+        self.add_column("sessions", "multiple_choice", "INT")
+            .await?;
+        self.add_column("moves", "choices", "VARCHAR(1024)").await?;
+        self.add_column("sessions", "nonfinite", "INT").await?;
+        self.add_column("moves", "ptcgender", "SMALLINT").await?;
+        self.add_column("moves", "ptccase", "SMALLINT").await?;
+        self.add_column("sessions", "share_token", "TEXT DEFAULT NULL")
+            .await?;
+        self.add_column("moves", "answerstarttimestamp", "BIGINT")
+            .await?;
+        self.alter_column_type("sessions", "challenger_score", "DOUBLE PRECISION")
+            .await?;
+        self.alter_column_type("sessions", "challenged_score", "DOUBLE PRECISION")
+            .await?;
+
         Ok(())
     }
 }
@@ -0,0 +1,364 @@
+use crate::hc_param_label;
+use crate::hc_strip_diacritics;
+use crate::CASE_LABELS;
+use crate::GENDER_LABELS;
+use crate::MOOD_LABELS;
+use crate::NUMBER_LABELS;
+use crate::PERSON_LABELS;
+use crate::TENSE_LABELS;
+use crate::VOICE_LABELS;
+use hoplite_verb_chooser::HcCase;
+use hoplite_verb_chooser::HcGender;
+use hoplite_verb_chooser::HcGreekVerb;
+use hoplite_verb_chooser::HcGreekVerbForm;
+use hoplite_verb_chooser::HcMood;
+use hoplite_verb_chooser::HcNumber;
+use hoplite_verb_chooser::HcPerson;
+use hoplite_verb_chooser::HcTense;
+use hoplite_verb_chooser::HcVerbForms;
+use hoplite_verb_chooser::HcVoice;
+use itertools::iproduct;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+//one parse of a form, with the same codes as moves and the synopsis:
+//infinitives have no person or number, participles have gender and case instead of person
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormParse {
+    pub verb: i32,
+    pub person: Option<i16>,
+    pub number: Option<i16>,
+    pub tense: i16,
+    pub voice: i16,
+    pub mood: i16,
+    pub gender: Option<i16>,
+    pub case: Option<i16>,
+}
+
+//every form of every verb, keyed by spelling, so a wrong answer can be recognized as some other form
+#[derive(Debug, Default)]
+pub struct FormIndex {
+    forms: HashMap<String, Vec<FormParse>>,
+    unaccented: HashMap<String, Vec<FormParse>>, //same forms without accents or breathings
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParseQuery {
+    pub form: String,
+    pub ignore_accents: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParseResult {
+    pub parse: FormParse,
+    pub lemma: String,
+    pub description: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParseResponse {
+    pub response_to: String,
+    pub success: bool,
+    pub form: String,
+    pub parses: Vec<ParseResult>,
+}
+
+//how a parse answer compares to the closest valid parse of the form
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseGrade {
+    pub verb: bool,
+    pub person: bool,
+    pub number: bool,
+    pub tense: bool,
+    pub voice: bool,
+    pub mood: bool,
+}
+
+impl ParseGrade {
+    fn right(&self) -> usize {
+        [
+            self.verb,
+            self.person,
+            self.number,
+            self.tense,
+            self.voice,
+            self.mood,
+        ]
+        .into_iter()
+        .filter(|r| *r)
+        .count()
+    }
+
+    //fraction of a point: a sixth for each parameter
+    pub fn score(&self) -> f64 {
+        self.right() as f64 / 6.0
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.right() == 6
+    }
+}
+
+impl FormIndex {
+    pub fn len(&self) -> usize {
+        self.forms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forms.is_empty()
+    }
+
+    fn insert(&mut self, form: &str, parse: FormParse) {
+        let key = hc_form_key(form);
+        if key.is_empty() || key == "—" {
+            return;
+        }
+        for parses in [
+            self.unaccented
+                .entry(hc_strip_diacritics(&key))
+                .or_default(),
+            self.forms.entry(key).or_default(),
+        ] {
+            if !parses.contains(&parse) {
+                parses.push(parse);
+            }
+        }
+    }
+
+    fn get(&self, form: &str, ignore_accents: bool) -> Option<&Vec<FormParse>> {
+        let key = hc_form_key(form);
+        if ignore_accents {
+            self.unaccented.get(&hc_strip_diacritics(&key))
+        } else {
+            self.forms.get(&key)
+        }
+    }
+}
+
+//forms are looked up ignoring vowel length marks, case and surrounding space
+fn hc_form_key(form: &str) -> String {
+    form.nfd()
+        .filter(|c| *c != '\u{0304}' && *c != '\u{0306}')
+        .nfc()
+        .collect::<String>()
+        .trim()
+        .to_lowercase()
+}
+
+//"ἔλυε(ν)" can be written with or without the movable nu
+fn hc_form_variants(form: &str) -> Vec<String> {
+    if form.contains('(') {
+        let mut without = String::new();
+        let mut in_parens = false;
+        for c in form.chars() {
+            match c {
+                '(' => in_parens = true,
+                ')' => in_parens = false,
+                _ if !in_parens => without.push(c),
+                _ => (),
+            }
+        }
+        vec![without, form.replace(['(', ')'], "")]
+    } else {
+        vec![form.to_string()]
+    }
+}
+
+//built once at startup: this generates every form of every verb, so it takes a moment
+pub fn hc_build_form_index(verbs: &[Arc<HcGreekVerb>]) -> FormIndex {
+    let persons = [HcPerson::First, HcPerson::Second, HcPerson::Third];
+    let numbers = [HcNumber::Singular, HcNumber::Plural];
+    let tenses = [
+        HcTense::Present,
+        HcTense::Imperfect,
+        HcTense::Future,
+        HcTense::Aorist,
+        HcTense::Perfect,
+        HcTense::Pluperfect,
+        HcTense::FuturePerfect,
+    ];
+    let voices = [HcVoice::Active, HcVoice::Middle, HcVoice::Passive];
+    let moods = [
+        HcMood::Indicative,
+        HcMood::Subjunctive,
+        HcMood::Optative,
+        HcMood::Imperative,
+    ];
+    let genders = [HcGender::Masculine, HcGender::Feminine, HcGender::Neuter];
+    let cases = [
+        HcCase::Nominative,
+        HcCase::Genitive,
+        HcCase::Dative,
+        HcCase::Accusative,
+        HcCase::Vocative,
+    ];
+
+    //mood, person, number, gender and case of every slot in a tense and voice
+    let mut slots = vec![];
+    for (mood, number, person) in iproduct!(moods, numbers, persons) {
+        slots.push((mood, Some(person), Some(number), None, None));
+    }
+    slots.push((HcMood::Infinitive, None, None, None, None));
+    for (number, gender, case) in iproduct!(
+        numbers,
+        genders.into_iter().enumerate(),
+        cases.into_iter().enumerate()
+    ) {
+        slots.push((
+            HcMood::Participle,
+            None,
+            Some(number),
+            Some(gender),
+            Some(case),
+        ));
+    }
+
+    let mut index = FormIndex::default();
+    //verbs[0] is a placeholder
+    for (verb_id, verb) in verbs.iter().enumerate().skip(1) {
+        for (tense, voice, (mood, person, number, gender, case)) in
+            iproduct!(tenses, voices, slots.iter().copied())
+        {
+            let vf = HcGreekVerbForm {
+                verb: verb.clone(),
+                person,
+                number,
+                tense,
+                voice,
+                mood,
+                gender: gender.map(|(_, g)| g),
+                case: case.map(|(_, c)| c),
+            };
+            if let Ok(steps) = vf.get_form(false) {
+                let parse = FormParse {
+                    verb: verb_id as i32,
+                    person: person.map(|p| p.to_i16()),
+                    number: number.map(|n| n.to_i16()),
+                    tense: tense.to_i16(),
+                    voice: voice.to_i16(),
+                    mood: mood.to_i16(),
+                    gender: gender.map(|(g, _)| g as i16),
+                    case: case.map(|(c, _)| c as i16),
+                };
+                for alt in steps.last().unwrap().form.split([',', '/']) {
+                    for f in hc_form_variants(alt) {
+                        index.insert(&f, parse);
+                    }
+                }
+            }
+        }
+    }
+    index
+}
+
+//all the parses of a form; alternatives separated by commas are each looked up
+pub fn hc_parse_form(index: &FormIndex, form: &str, ignore_accents: bool) -> Vec<FormParse> {
+    form.split(',')
+        .filter_map(|f| index.get(f, ignore_accents))
+        .flatten()
+        .copied()
+        .unique()
+        .collect()
+}
+
+//e.g. "aorist middle indicative second singular of λύω" or "present active participle masculine genitive plural of λύω"
+pub fn hc_describe_parse(parse: &FormParse, verbs: &[Arc<HcGreekVerb>]) -> String {
+    let labels = [
+        hc_param_label(&TENSE_LABELS, Some(parse.tense)),
+        hc_param_label(&VOICE_LABELS, Some(parse.voice)),
+        hc_param_label(&MOOD_LABELS, Some(parse.mood)),
+        hc_param_label(&PERSON_LABELS, parse.person),
+        hc_param_label(&GENDER_LABELS, parse.gender),
+        hc_param_label(&CASE_LABELS, parse.case),
+        hc_param_label(&NUMBER_LABELS, parse.number),
+    ];
+    format!(
+        "{} of {}",
+        labels.into_iter().flatten().join(" "),
+        hc_lemma(parse, verbs)
+    )
+}
+
+fn hc_lemma(parse: &FormParse, verbs: &[Arc<HcGreekVerb>]) -> String {
+    verbs
+        .get(parse.verb as usize)
+        .map(|v| v.pps[0].clone())
+        .unwrap_or_default()
+}
+
+//what a wrong answer actually is, if it is a real form other than the one that was asked for
+pub fn hc_diagnose_answer(
+    index: &FormIndex,
+    verbs: &[Arc<HcGreekVerb>],
+    answer: &str,
+    asked: Option<&FormParse>,
+) -> Option<String> {
+    let parses = hc_parse_form(index, answer, false)
+        .into_iter()
+        .filter(|p| Some(p) != asked)
+        .map(|p| hc_describe_parse(&p, verbs))
+        .collect::<Vec<_>>();
+    if parses.is_empty() {
+        None
+    } else {
+        Some(format!("your answer is the {}", parses.join(" or ")))
+    }
+}
+
+//every parse of a form among the loaded verbs, as a reference tool
+pub fn hc_parse(index: &FormIndex, verbs: &[Arc<HcGreekVerb>], info: &ParseQuery) -> ParseResponse {
+    let parses = hc_parse_form(index, &info.form, info.ignore_accents.unwrap_or(false))
+        .into_iter()
+        .map(|p| ParseResult {
+            parse: p,
+            lemma: hc_lemma(&p, verbs),
+            description: hc_describe_parse(&p, verbs),
+        })
+        .collect();
+    ParseResponse {
+        response_to: String::from("parse"),
+        success: true,
+        form: info.form.clone(),
+        parses,
+    }
+}
+
+//every parse of a generated form: the one that was asked plus any others it shares a spelling with
+pub fn hc_valid_parses(index: Option<&FormIndex>, form: &str, asked: FormParse) -> Vec<FormParse> {
+    let mut parses = vec![asked];
+    if let Some(index) = index {
+        for alt in form.split([',', '/']) {
+            for f in hc_form_variants(alt) {
+                parses.extend(hc_parse_form(index, &f, false));
+            }
+        }
+    }
+    parses.into_iter().unique().collect()
+}
+
+//each parameter is scored against whichever valid parse the answer comes closest to
+pub fn hc_grade_parse(given: &FormParse, valid: &[FormParse]) -> ParseGrade {
+    valid
+        .iter()
+        .map(|p| ParseGrade {
+            verb: given.verb == p.verb,
+            person: given.person == p.person,
+            number: given.number == p.number,
+            tense: given.tense == p.tense,
+            voice: given.voice == p.voice,
+            mood: given.mood == p.mood,
+        })
+        .max_by_key(|g| g.right())
+        .unwrap_or(ParseGrade {
+            verb: false,
+            person: false,
+            number: false,
+            tense: false,
+            voice: false,
+            mood: false,
+        })
+}
@@ -1,12 +1,15 @@
 //https://tokio.rs/tokio/topics/bridging
 use crate::get_timestamp;
+use crate::hc_accept_invitation;
 use crate::hc_answer;
 use crate::hc_ask;
 use crate::hc_create_db;
 use crate::hc_create_user;
+use crate::hc_decline_invitation;
 use crate::hc_get_game_moves;
 use crate::hc_get_move;
 use crate::hc_get_sessions;
+use crate::hc_get_user_stats;
 use crate::hc_insert_session;
 use crate::hc_mf_pressed;
 use crate::hc_validate_credentials;
@@ -21,6 +24,8 @@ use crate::HcGreekVerb;
 use crate::MoveResult;
 use crate::SessionState;
 use crate::SessionsListResponse;
+use crate::UserStatsQuery;
+use crate::UserStatsResponse;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -87,6 +92,7 @@ impl HcBlockingClient {
             verbs,
             username,
             get_sessions,
+            get_timestamp(),
         ))
     }
 
@@ -105,6 +111,32 @@ impl HcBlockingClient {
         ))
     }
 
+    pub fn accept_invitation(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<SessionState, HcError> {
+        self.rt.block_on(hc_accept_invitation(
+            &self.inner_db,
+            user_id,
+            session_id,
+            get_timestamp(),
+        ))
+    }
+
+    pub fn decline_invitation(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<SessionState, HcError> {
+        self.rt.block_on(hc_decline_invitation(
+            &self.inner_db,
+            user_id,
+            session_id,
+            get_timestamp(),
+        ))
+    }
+
     pub fn get_move(
         &self,
         user_id: Uuid,
@@ -128,6 +160,15 @@ impl HcBlockingClient {
             .block_on(hc_get_game_moves(&self.inner_db, get_moves_query))
     }
 
+    pub fn get_user_stats(
+        &self,
+        user_id: Uuid,
+        stats_query: &UserStatsQuery,
+    ) -> Result<UserStatsResponse, HcError> {
+        self.rt
+            .block_on(hc_get_user_stats(&self.inner_db, user_id, stats_query))
+    }
+
     pub fn answer(
         &self,
         user_id: Uuid,
@@ -140,6 +181,7 @@ impl HcBlockingClient {
             answer_query,
             get_timestamp(),
             verbs,
+            None,
         ))
     }
 
@@ -0,0 +1,287 @@
+use crate::AnswerQuery;
+use crate::AskQuery;
+use crate::ChatHistoryResponse;
+use crate::ChatQuery;
+use crate::ChatResponse;
+use crate::CreateSessionQuery;
+use crate::GetMoveQuery;
+use crate::GetSessions;
+use crate::HcError;
+use crate::ParseAnswerQuery;
+use crate::RemoveChatQuery;
+use crate::SessionState;
+use crate::SessionsListResponse;
+use crate::ShareResponse;
+use crate::SpectateQuery;
+use serde::Deserialize;
+use serde::Serialize;
+use uuid::Uuid;
+
+//bumped whenever a request or response changes shape, so old clients get an error instead of a misread
+pub const HC_PROTOCOL_VERSION: u32 = 1;
+
+//a websocket request: the same query structs the http routes take, picked by their qtype.
+//qtype is read as the tag here, so the structs' own qtype fields are left empty and never written
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "qtype", rename_all = "lowercase")]
+pub enum WsRequest {
+    GetMove(GetMoveQuery),
+    NewSession(CreateSessionQuery),
+    Ask(AskQuery),
+    StartAnswer(GetMoveQuery),
+    Submit(AnswerQuery),
+    MfPressed(AnswerQuery),
+    ParseAnswer(ParseAnswerQuery),
+    GetSessions(GetSessions),
+    AcceptInvitation(GetMoveQuery),
+    DeclineInvitation(GetMoveQuery),
+    Typing(TypingQuery),
+    Chat(ChatQuery),
+    GetChat(GetMoveQuery),
+    RemoveChat(RemoveChatQuery),
+    Share(GetMoveQuery),
+    Spectate(SpectateQuery),
+    //any qtype this server does not know: hc_parse_ws_request answers it with an error frame
+    #[serde(other)]
+    Unknown,
+}
+
+impl WsRequest {
+    pub fn qtype(&self) -> &'static str {
+        match self {
+            WsRequest::GetMove(_) => "getmove",
+            WsRequest::NewSession(_) => "newsession",
+            WsRequest::Ask(_) => "ask",
+            WsRequest::StartAnswer(_) => "startanswer",
+            WsRequest::Submit(_) => "submit",
+            WsRequest::MfPressed(_) => "mfpressed",
+            WsRequest::ParseAnswer(_) => "parseanswer",
+            WsRequest::GetSessions(_) => "getsessions",
+            WsRequest::AcceptInvitation(_) => "acceptinvitation",
+            WsRequest::DeclineInvitation(_) => "declineinvitation",
+            WsRequest::Typing(_) => "typing",
+            WsRequest::Chat(_) => "chat",
+            WsRequest::GetChat(_) => "getchat",
+            WsRequest::RemoveChat(_) => "removechat",
+            WsRequest::Share(_) => "share",
+            WsRequest::Spectate(_) => "spectate",
+            WsRequest::Unknown => "unknown",
+        }
+    }
+}
+
+//a player started or stopped answering; relayed to the rest of the game room, with no reply
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TypingQuery {
+    pub session_id: Uuid,
+    pub typing: bool,
+}
+
+//the id is chosen by the client and echoed on the reply, so it can match replies to requests
+#[derive(Deserialize, Serialize)]
+pub struct WsRequestFrame {
+    pub version: u32,
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub request: WsRequest,
+}
+
+//the fields every request carries, read first so errors can name the request they answer
+#[derive(Deserialize)]
+struct WsEnvelope {
+    version: Option<u32>,
+    id: Option<u64>,
+    qtype: Option<String>,
+}
+
+//everything the server sends over a websocket: each response names itself in its response_to
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum WsResponse {
+    SessionState(SessionState),
+    Status(StatusResponse),
+    Sessions(SessionsListResponse),
+    Chat(ChatResponse),
+    ChatHistory(ChatHistoryResponse),
+    Share(ShareResponse),
+    Presence(PresenceResponse),
+    Typing(TypingResponse),
+}
+
+impl From<SessionState> for WsResponse {
+    fn from(res: SessionState) -> WsResponse {
+        WsResponse::SessionState(res)
+    }
+}
+
+impl From<StatusResponse> for WsResponse {
+    fn from(res: StatusResponse) -> WsResponse {
+        WsResponse::Status(res)
+    }
+}
+
+impl From<SessionsListResponse> for WsResponse {
+    fn from(res: SessionsListResponse) -> WsResponse {
+        WsResponse::Sessions(res)
+    }
+}
+
+impl From<ChatResponse> for WsResponse {
+    fn from(res: ChatResponse) -> WsResponse {
+        WsResponse::Chat(res)
+    }
+}
+
+impl From<ChatHistoryResponse> for WsResponse {
+    fn from(res: ChatHistoryResponse) -> WsResponse {
+        WsResponse::ChatHistory(res)
+    }
+}
+
+impl From<ShareResponse> for WsResponse {
+    fn from(res: ShareResponse) -> WsResponse {
+        WsResponse::Share(res)
+    }
+}
+
+impl From<PresenceResponse> for WsResponse {
+    fn from(res: PresenceResponse) -> WsResponse {
+        WsResponse::Presence(res)
+    }
+}
+
+impl From<TypingResponse> for WsResponse {
+    fn from(res: TypingResponse) -> WsResponse {
+        WsResponse::Typing(res)
+    }
+}
+
+#[derive(Serialize)]
+pub struct WsResponseFrame {
+    pub version: u32,
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub response: WsResponse,
+}
+
+impl WsResponseFrame {
+    //id is None for messages the server pushes without being asked, e.g. an opponent's move
+    pub fn new(id: Option<u64>, response: impl Into<WsResponse>) -> WsResponseFrame {
+        WsResponseFrame {
+            version: HC_PROTOCOL_VERSION,
+            id,
+            response: response.into(),
+        }
+    }
+}
+
+//a plain reply saying whether a request worked, e.g. creating a game
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatusResponse {
+    pub response_to: String,
+    pub mesg: String,
+    pub success: bool,
+}
+
+//pushed to the rest of a game room when a player opens or leaves the game
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PresenceResponse {
+    pub response_to: String,
+    pub session_id: Uuid,
+    pub present: bool,
+}
+
+impl PresenceResponse {
+    pub fn new(session_id: Uuid, present: bool) -> PresenceResponse {
+        PresenceResponse {
+            response_to: String::from("presence"),
+            session_id,
+            present,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TypingResponse {
+    pub response_to: String,
+    pub session_id: Uuid,
+    pub typing: bool,
+}
+
+impl TypingResponse {
+    pub fn new(session_id: Uuid, typing: bool) -> TypingResponse {
+        TypingResponse {
+            response_to: String::from("typing"),
+            session_id,
+            typing,
+        }
+    }
+}
+
+//sent instead of a response when a request cannot be read or fails;
+//error is a machine-readable code like the http error bodies, message is for people
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WsErrorFrame {
+    pub version: u32,
+    pub id: Option<u64>,
+    pub response_to: String,
+    pub qtype: Option<String>,
+    pub error: String,
+    pub message: String,
+    pub success: bool,
+}
+
+impl WsErrorFrame {
+    fn new(id: Option<u64>, qtype: Option<String>, error: &str, message: String) -> WsErrorFrame {
+        WsErrorFrame {
+            version: HC_PROTOCOL_VERSION,
+            id,
+            response_to: String::from("error"),
+            qtype,
+            error: String::from(error),
+            message,
+            success: false,
+        }
+    }
+
+    pub fn from_hc_error(id: Option<u64>, qtype: &str, e: &HcError) -> WsErrorFrame {
+        WsErrorFrame::new(id, Some(String::from(qtype)), e.code(), e.to_string())
+    }
+}
+
+pub fn hc_parse_ws_request(text: &str) -> Result<WsRequestFrame, WsErrorFrame> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| WsErrorFrame::new(None, None, "invalid_json", e.to_string()))?;
+    let envelope = WsEnvelope::deserialize(&value)
+        .map_err(|e| WsErrorFrame::new(None, None, "invalid_request", e.to_string()))?;
+    let id = envelope.id;
+    let qtype = envelope.qtype;
+
+    match envelope.version {
+        Some(HC_PROTOCOL_VERSION) => (),
+        Some(v) => {
+            return Err(WsErrorFrame::new(
+                id,
+                qtype,
+                "unsupported_version",
+                format!("protocol version {v} is not supported, expected {HC_PROTOCOL_VERSION}"),
+            ))
+        }
+        None => {
+            return Err(WsErrorFrame::new(
+                id,
+                qtype,
+                "unsupported_version",
+                format!("request has no protocol version, expected {HC_PROTOCOL_VERSION}"),
+            ))
+        }
+    }
+
+    let frame: WsRequestFrame = serde_json::from_value(value)
+        .map_err(|e| WsErrorFrame::new(id, qtype.clone(), "invalid_request", e.to_string()))?;
+    if let WsRequest::Unknown = frame.request {
+        let message = format!("unknown qtype: {}", qtype.as_deref().unwrap_or_default());
+        return Err(WsErrorFrame::new(id, qtype, "unknown_request", message));
+    }
+    Ok(frame)
+}
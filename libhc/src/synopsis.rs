@@ -1,5 +1,20 @@
-use crate::hgk_compare_multiple_forms;
+use crate::formindex::hc_diagnose_answer;
+use crate::formindex::FormIndex;
+use crate::hc_derivation;
+use crate::hc_grade_answer;
+use crate::hc_param_label;
+use crate::hc_verb_option;
+use crate::AnswerGrade;
+use crate::DerivationStep;
 use crate::HcDb;
+use crate::HcError;
+use crate::CASE_LABELS;
+use crate::GENDER_LABELS;
+use crate::MOOD_LABELS;
+use crate::NUMBER_LABELS;
+use crate::PERSON_LABELS;
+use crate::TENSE_LABELS;
+use crate::VOICE_LABELS;
 
 use hoplite_verb_chooser::check_pps;
 use hoplite_verb_chooser::HcCase;
@@ -17,7 +32,9 @@ use serde::Deserialize;
 use serde::Serialize;
 use uuid::Uuid;
 
+use itertools::iproduct;
 use itertools::Itertools;
+use rand::prelude::SliceRandom;
 use sqlx::FromRow;
 use std::sync::Arc;
 
@@ -36,6 +53,16 @@ pub struct SynopsisSaverRequest {
     pub ptcnumber: Option<i32>,
     pub r: Vec<String>,
     pub verb: i32,
+    #[serde(default)]
+    pub grades: String, //AnswerGrade codes, one per form, filled in when saving
+    #[serde(default)]
+    pub score: String,
+    #[serde(default)]
+    pub tense: Option<i32>, //tense, voice and mood of a paradigm drill
+    #[serde(default)]
+    pub voice: Option<i32>,
+    #[serde(default)]
+    pub mood: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -310,6 +337,48 @@ pub struct GreekSynopsisResult {
     pub f62: String,
     pub a62: String,
     pub c62: bool,
+    pub grades: Option<String>,
+}
+
+//a paradigm drill: the six persons and numbers of one tense, voice and mood
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct GreekParadigmResult {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub updated: Option<chrono::NaiveDateTime>,
+    pub sname: String,
+    pub advisor: String,
+    pub sgiday: i32,
+    pub selectedverb: String,
+    pub pp: String,
+    pub pp_correct: String,
+    pub pp_is_correct: String,
+    pub verbtense: i32,
+    pub verbvoice: i32,
+    pub verbmood: i32,
+    pub ip: String,
+    pub ua: String,
+    pub status: i32,
+    pub score: String,
+    pub grades: String,
+    pub f0: String,
+    pub a0: String,
+    pub c0: bool,
+    pub f1: String,
+    pub a1: String,
+    pub c1: bool,
+    pub f2: String,
+    pub a2: String,
+    pub c2: bool,
+    pub f3: String,
+    pub a3: String,
+    pub c3: bool,
+    pub f4: String,
+    pub a4: String,
+    pub c4: bool,
+    pub f5: String,
+    pub a5: String,
+    pub c5: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -317,6 +386,23 @@ pub struct SaverResults {
     pub given: String,
     pub correct: String,
     pub is_correct: bool,
+    pub grade: Option<AnswerGrade>,
+    pub derivation: Option<Vec<DerivationStep>>, //only set for wrong answers when saving
+    pub diagnosis: Option<String>, //only set for wrong answers which are some other form
+}
+
+impl SaverResults {
+    //an ungraded result, as shown before saving or as stored by an earlier version
+    pub fn new(given: String, correct: String, is_correct: bool) -> SaverResults {
+        SaverResults {
+            given,
+            correct,
+            is_correct,
+            grade: None,
+            derivation: None,
+            diagnosis: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone, FromRow)]
@@ -332,6 +418,10 @@ pub struct SynopsisJsonResult {
     pub pp_is_correct: String,
     pub name: String,
     pub advisor: String,
+    pub score: Option<f64>, //partial-credit total over the forms that exist
+    pub tense: Option<i32>, //only for paradigm drills
+    pub voice: Option<i32>,
+    pub mood: Option<i32>,
     pub f: Vec<SaverResults>,
 }
 
@@ -352,11 +442,11 @@ pub fn get_synopsis(
 
     let mut res = Vec::<SaverResults>::new();
     for f in forms {
-        res.push(SaverResults {
-            given: f.unwrap_or("".to_string()),
-            correct: String::from(""),
-            is_correct: true,
-        });
+        res.push(SaverResults::new(
+            f.unwrap_or("".to_string()),
+            String::from(""),
+            true,
+        ));
     }
 
     SynopsisJsonResult {
@@ -379,6 +469,10 @@ pub fn get_synopsis(
         pp_is_correct: "".to_string(),
         name: "".to_string(),
         advisor: "".to_string(),
+        score: None,
+        tense: None,
+        voice: None,
+        mood: None,
         f: res,
     }
 }
@@ -389,324 +483,81 @@ pub async fn get_synopsis_result(id: Uuid, hcdb: &dyn HcDb) -> Option<SynopsisJs
         //need to store is_correct and correct/incorrect answers
         tx.commit_tx().await.unwrap();
 
-        let res_forms = vec![
-            SaverResults {
-                given: result.f0,
-                correct: result.a0,
-                is_correct: result.c0,
-            },
-            SaverResults {
-                given: result.f1,
-                correct: result.a1,
-                is_correct: result.c1,
-            },
-            SaverResults {
-                given: result.f2,
-                correct: result.a2,
-                is_correct: result.c2,
-            },
-            SaverResults {
-                given: result.f3,
-                correct: result.a3,
-                is_correct: result.c3,
-            },
-            SaverResults {
-                given: result.f4,
-                correct: result.a4,
-                is_correct: result.c4,
-            },
-            SaverResults {
-                given: result.f5,
-                correct: result.a5,
-                is_correct: result.c5,
-            },
-            SaverResults {
-                given: result.f6,
-                correct: result.a6,
-                is_correct: result.c6,
-            },
-            SaverResults {
-                given: result.f7,
-                correct: result.a7,
-                is_correct: result.c7,
-            },
-            SaverResults {
-                given: result.f8,
-                correct: result.a8,
-                is_correct: result.c8,
-            },
-            SaverResults {
-                given: result.f9,
-                correct: result.a9,
-                is_correct: result.c9,
-            },
-            SaverResults {
-                given: result.f10,
-                correct: result.a10,
-                is_correct: result.c10,
-            },
-            SaverResults {
-                given: result.f11,
-                correct: result.a11,
-                is_correct: result.c11,
-            },
-            SaverResults {
-                given: result.f12,
-                correct: result.a12,
-                is_correct: result.c12,
-            },
-            SaverResults {
-                given: result.f13,
-                correct: result.a13,
-                is_correct: result.c13,
-            },
-            SaverResults {
-                given: result.f14,
-                correct: result.a14,
-                is_correct: result.c14,
-            },
-            SaverResults {
-                given: result.f15,
-                correct: result.a15,
-                is_correct: result.c15,
-            },
-            SaverResults {
-                given: result.f16,
-                correct: result.a16,
-                is_correct: result.c16,
-            },
-            SaverResults {
-                given: result.f17,
-                correct: result.a17,
-                is_correct: result.c17,
-            },
-            SaverResults {
-                given: result.f18,
-                correct: result.a18,
-                is_correct: result.c18,
-            },
-            SaverResults {
-                given: result.f19,
-                correct: result.a19,
-                is_correct: result.c19,
-            },
-            SaverResults {
-                given: result.f20,
-                correct: result.a20,
-                is_correct: result.c20,
-            },
-            SaverResults {
-                given: result.f21,
-                correct: result.a21,
-                is_correct: result.c21,
-            },
-            SaverResults {
-                given: result.f22,
-                correct: result.a22,
-                is_correct: result.c22,
-            },
-            SaverResults {
-                given: result.f23,
-                correct: result.a23,
-                is_correct: result.c23,
-            },
-            SaverResults {
-                given: result.f24,
-                correct: result.a24,
-                is_correct: result.c24,
-            },
-            SaverResults {
-                given: result.f25,
-                correct: result.a25,
-                is_correct: result.c25,
-            },
-            SaverResults {
-                given: result.f26,
-                correct: result.a26,
-                is_correct: result.c26,
-            },
-            SaverResults {
-                given: result.f27,
-                correct: result.a27,
-                is_correct: result.c27,
-            },
-            SaverResults {
-                given: result.f28,
-                correct: result.a28,
-                is_correct: result.c28,
-            },
-            SaverResults {
-                given: result.f29,
-                correct: result.a29,
-                is_correct: result.c29,
-            },
-            SaverResults {
-                given: result.f30,
-                correct: result.a30,
-                is_correct: result.c30,
-            },
-            SaverResults {
-                given: result.f31,
-                correct: result.a31,
-                is_correct: result.c31,
-            },
-            SaverResults {
-                given: result.f32,
-                correct: result.a32,
-                is_correct: result.c32,
-            },
-            SaverResults {
-                given: result.f33,
-                correct: result.a33,
-                is_correct: result.c33,
-            },
-            SaverResults {
-                given: result.f34,
-                correct: result.a34,
-                is_correct: result.c34,
-            },
-            SaverResults {
-                given: result.f35,
-                correct: result.a35,
-                is_correct: result.c35,
-            },
-            SaverResults {
-                given: result.f36,
-                correct: result.a36,
-                is_correct: result.c36,
-            },
-            SaverResults {
-                given: result.f37,
-                correct: result.a37,
-                is_correct: result.c37,
-            },
-            SaverResults {
-                given: result.f38,
-                correct: result.a38,
-                is_correct: result.c38,
-            },
-            SaverResults {
-                given: result.f39,
-                correct: result.a39,
-                is_correct: result.c39,
-            },
-            SaverResults {
-                given: result.f40,
-                correct: result.a40,
-                is_correct: result.c40,
-            },
-            SaverResults {
-                given: result.f41,
-                correct: result.a41,
-                is_correct: result.c41,
-            },
-            SaverResults {
-                given: result.f42,
-                correct: result.a42,
-                is_correct: result.c42,
-            },
-            SaverResults {
-                given: result.f43,
-                correct: result.a43,
-                is_correct: result.c43,
-            },
-            SaverResults {
-                given: result.f44,
-                correct: result.a44,
-                is_correct: result.c44,
-            },
-            SaverResults {
-                given: result.f45,
-                correct: result.a45,
-                is_correct: result.c45,
-            },
-            SaverResults {
-                given: result.f46,
-                correct: result.a46,
-                is_correct: result.c46,
-            },
-            SaverResults {
-                given: result.f47,
-                correct: result.a47,
-                is_correct: result.c47,
-            },
-            SaverResults {
-                given: result.f48,
-                correct: result.a48,
-                is_correct: result.c48,
-            },
-            SaverResults {
-                given: result.f49,
-                correct: result.a49,
-                is_correct: result.c49,
-            },
-            SaverResults {
-                given: result.f50,
-                correct: result.a50,
-                is_correct: result.c50,
-            },
-            SaverResults {
-                given: result.f51,
-                correct: result.a51,
-                is_correct: result.c51,
-            },
-            SaverResults {
-                given: result.f52,
-                correct: result.a52,
-                is_correct: result.c52,
-            },
-            SaverResults {
-                given: result.f53,
-                correct: result.a53,
-                is_correct: result.c53,
-            },
-            SaverResults {
-                given: result.f54,
-                correct: result.a54,
-                is_correct: result.c54,
-            },
-            SaverResults {
-                given: result.f55,
-                correct: result.a55,
-                is_correct: result.c55,
-            },
-            SaverResults {
-                given: result.f56,
-                correct: result.a56,
-                is_correct: result.c56,
-            },
-            SaverResults {
-                given: result.f57,
-                correct: result.a57,
-                is_correct: result.c57,
-            },
-            SaverResults {
-                given: result.f58,
-                correct: result.a58,
-                is_correct: result.c58,
-            },
-            SaverResults {
-                given: result.f59,
-                correct: result.a59,
-                is_correct: result.c59,
-            },
-            SaverResults {
-                given: result.f60,
-                correct: result.a60,
-                is_correct: result.c60,
-            },
-            SaverResults {
-                given: result.f61,
-                correct: result.a61,
-                is_correct: result.c61,
-            },
-            SaverResults {
-                given: result.f62,
-                correct: result.a62,
-                is_correct: result.c62,
-            },
+        let mut res_forms = vec![
+            SaverResults::new(result.f0, result.a0, result.c0),
+            SaverResults::new(result.f1, result.a1, result.c1),
+            SaverResults::new(result.f2, result.a2, result.c2),
+            SaverResults::new(result.f3, result.a3, result.c3),
+            SaverResults::new(result.f4, result.a4, result.c4),
+            SaverResults::new(result.f5, result.a5, result.c5),
+            SaverResults::new(result.f6, result.a6, result.c6),
+            SaverResults::new(result.f7, result.a7, result.c7),
+            SaverResults::new(result.f8, result.a8, result.c8),
+            SaverResults::new(result.f9, result.a9, result.c9),
+            SaverResults::new(result.f10, result.a10, result.c10),
+            SaverResults::new(result.f11, result.a11, result.c11),
+            SaverResults::new(result.f12, result.a12, result.c12),
+            SaverResults::new(result.f13, result.a13, result.c13),
+            SaverResults::new(result.f14, result.a14, result.c14),
+            SaverResults::new(result.f15, result.a15, result.c15),
+            SaverResults::new(result.f16, result.a16, result.c16),
+            SaverResults::new(result.f17, result.a17, result.c17),
+            SaverResults::new(result.f18, result.a18, result.c18),
+            SaverResults::new(result.f19, result.a19, result.c19),
+            SaverResults::new(result.f20, result.a20, result.c20),
+            SaverResults::new(result.f21, result.a21, result.c21),
+            SaverResults::new(result.f22, result.a22, result.c22),
+            SaverResults::new(result.f23, result.a23, result.c23),
+            SaverResults::new(result.f24, result.a24, result.c24),
+            SaverResults::new(result.f25, result.a25, result.c25),
+            SaverResults::new(result.f26, result.a26, result.c26),
+            SaverResults::new(result.f27, result.a27, result.c27),
+            SaverResults::new(result.f28, result.a28, result.c28),
+            SaverResults::new(result.f29, result.a29, result.c29),
+            SaverResults::new(result.f30, result.a30, result.c30),
+            SaverResults::new(result.f31, result.a31, result.c31),
+            SaverResults::new(result.f32, result.a32, result.c32),
+            SaverResults::new(result.f33, result.a33, result.c33),
+            SaverResults::new(result.f34, result.a34, result.c34),
+            SaverResults::new(result.f35, result.a35, result.c35),
+            SaverResults::new(result.f36, result.a36, result.c36),
+            SaverResults::new(result.f37, result.a37, result.c37),
+            SaverResults::new(result.f38, result.a38, result.c38),
+            SaverResults::new(result.f39, result.a39, result.c39),
+            SaverResults::new(result.f40, result.a40, result.c40),
+            SaverResults::new(result.f41, result.a41, result.c41),
+            SaverResults::new(result.f42, result.a42, result.c42),
+            SaverResults::new(result.f43, result.a43, result.c43),
+            SaverResults::new(result.f44, result.a44, result.c44),
+            SaverResults::new(result.f45, result.a45, result.c45),
+            SaverResults::new(result.f46, result.a46, result.c46),
+            SaverResults::new(result.f47, result.a47, result.c47),
+            SaverResults::new(result.f48, result.a48, result.c48),
+            SaverResults::new(result.f49, result.a49, result.c49),
+            SaverResults::new(result.f50, result.a50, result.c50),
+            SaverResults::new(result.f51, result.a51, result.c51),
+            SaverResults::new(result.f52, result.a52, result.c52),
+            SaverResults::new(result.f53, result.a53, result.c53),
+            SaverResults::new(result.f54, result.a54, result.c54),
+            SaverResults::new(result.f55, result.a55, result.c55),
+            SaverResults::new(result.f56, result.a56, result.c56),
+            SaverResults::new(result.f57, result.a57, result.c57),
+            SaverResults::new(result.f58, result.a58, result.c58),
+            SaverResults::new(result.f59, result.a59, result.c59),
+            SaverResults::new(result.f60, result.a60, result.c60),
+            SaverResults::new(result.f61, result.a61, result.c61),
+            SaverResults::new(result.f62, result.a62, result.c62),
         ];
 
+        //results saved before grading was stored have no grades
+        let mut score = None;
+        if let Some(grades) = &result.grades {
+            for (f, g) in res_forms.iter_mut().zip(grades.split(',')) {
+                f.grade = g.parse::<i32>().ok().map(AnswerGrade::from_i32);
+            }
+            score = result.score.parse::<f64>().ok();
+        }
+
         let res = SynopsisJsonResult {
             verb_id: result.selectedverb.parse::<i32>().unwrap(),
             person: result.verbperson.parse::<i32>().unwrap(),
@@ -733,6 +584,10 @@ pub async fn get_synopsis_result(id: Uuid, hcdb: &dyn HcDb) -> Option<SynopsisJs
             //     .join(", "),
             name: result.sname.clone(),
             advisor: result.advisor.clone(),
+            score,
+            tense: None,
+            voice: None,
+            mood: None,
             f: res_forms,
         };
         return Some(res);
@@ -744,10 +599,11 @@ pub async fn save_synopsis(
     mut payload: SynopsisSaverRequest,
     user_id: Option<Uuid>,
     verbs: &[Arc<HcGreekVerb>],
+    form_index: Option<&FormIndex>,
     hcdb: &dyn HcDb,
 ) -> Result<SynopsisJsonResult, Box<dyn std::error::Error>> {
     let verb_id = payload.verb.try_into().unwrap();
-    let correct_answers = get_forms(
+    let derivations = get_form_derivations(
         verbs,
         verb_id,
         payload.person,
@@ -755,30 +611,15 @@ pub async fn save_synopsis(
         payload.ptccase,
         payload.ptcgender,
     );
-    let mut is_correct = Vec::new();
-
-    for (i, f) in payload.r.iter().enumerate() {
-        if let Some(a) = &correct_answers[i] {
-            is_correct.push(hgk_compare_multiple_forms(a, &f.replace("---", "—"), true));
-        } else {
-            is_correct.push(true);
-        }
-    }
+    let (res_forms, score) = grade_forms(&payload.r, derivations, verbs, form_index);
 
     let is_correct_pps: Vec<bool> = check_pps(&payload.pp, &verbs[verb_id]);
 
     let mut db_insert = Vec::<String>::new();
-
-    let mut res_forms = Vec::<SaverResults>::new();
-    for (n, i) in correct_answers.into_iter().enumerate() {
-        res_forms.push(SaverResults {
-            given: payload.r[n].clone(),
-            correct: i.clone().unwrap_or(String::from("")),
-            is_correct: is_correct[n],
-        });
-        db_insert.push(payload.r[n].clone());
-        db_insert.push(i.unwrap_or(String::from("")));
-        db_insert.push(is_correct[n].to_string());
+    for f in res_forms.iter() {
+        db_insert.push(f.given.clone());
+        db_insert.push(f.correct.clone());
+        db_insert.push(f.is_correct.to_string());
     }
 
     let res = SynopsisJsonResult {
@@ -789,15 +630,7 @@ pub async fn save_synopsis(
         gender: payload.ptcgender,
         unit: payload.unit,
         pp: payload.pp.clone(),
-        pp_correct: format!(
-            "{}, {}, {}, {}, {}, {}",
-            &verbs[verb_id].pps[0],
-            &verbs[verb_id].pps[1],
-            &verbs[verb_id].pps[2],
-            &verbs[verb_id].pps[3],
-            &verbs[verb_id].pps[4],
-            &verbs[verb_id].pps[5]
-        ),
+        pp_correct: hc_pp_correct(&verbs[verb_id]),
         pp_is_correct: is_correct_pps
             .into_iter()
             .map(|x| (x as i32).to_string())
@@ -810,6 +643,10 @@ pub async fn save_synopsis(
         //     .join(", "),
         name: payload.sname.clone(),
         advisor: payload.advisor.clone(),
+        score: Some(score),
+        tense: None,
+        voice: None,
+        mood: None,
         f: res_forms,
     };
 
@@ -818,6 +655,8 @@ pub async fn save_synopsis(
     payload.r = db_insert; //add correct boolean and correct answers here to save to db
     payload.pp_correct.clone_from(&res.pp_correct);
     payload.pp_is_correct.clone_from(&res.pp_is_correct);
+    payload.grades = hc_grades_string(&res.f);
+    payload.score = score.to_string();
 
     tx.greek_insert_synopsis(
         user_id, &payload,
@@ -831,6 +670,57 @@ pub async fn save_synopsis(
     Ok(res)
 }
 
+//grades each given form against the correct one, for both synopses and paradigms.
+//forms that don't exist are always correct
+fn grade_forms(
+    given: &[String],
+    derivations: Vec<Option<Vec<DerivationStep>>>,
+    verbs: &[Arc<HcGreekVerb>],
+    form_index: Option<&FormIndex>,
+) -> (Vec<SaverResults>, f64) {
+    let mut res_forms = Vec::<SaverResults>::new();
+    let mut score = 0.0;
+    for (f, d) in given.iter().zip(derivations) {
+        let correct = d.as_ref().map(|steps| hc_derivation_form(steps));
+        let grade = correct.as_ref().map(|a| hc_grade_answer(a, f));
+        let is_correct = grade.is_none_or(|g| g.is_correct(false));
+        score += grade.map_or(0.0, |g| g.score());
+
+        res_forms.push(SaverResults {
+            given: f.clone(),
+            correct: correct.unwrap_or_default(),
+            is_correct,
+            grade,
+            derivation: if is_correct && grade != Some(AnswerGrade::AccentError) {
+                None
+            } else {
+                d
+            },
+            diagnosis: if is_correct {
+                None
+            } else {
+                form_index.and_then(|fi| hc_diagnose_answer(fi, verbs, f, None))
+            },
+        });
+    }
+    (res_forms, score)
+}
+
+//forms that don't exist are stored as exact
+fn hc_grades_string(forms: &[SaverResults]) -> String {
+    forms
+        .iter()
+        .map(|f| f.grade.unwrap_or(AnswerGrade::Exact).to_i32().to_string())
+        .join(",")
+}
+
+fn hc_pp_correct(verb: &HcGreekVerb) -> String {
+    format!(
+        "{}, {}, {}, {}, {}, {}",
+        &verb.pps[0], &verb.pps[1], &verb.pps[2], &verb.pps[3], &verb.pps[4], &verb.pps[5]
+    )
+}
+
 pub fn get_forms(
     verbs: &[Arc<HcGreekVerb>],
     verb_id: usize,
@@ -839,6 +729,26 @@ pub fn get_forms(
     case: Option<i32>,
     gender: Option<i32>,
 ) -> Vec<Option<String>> {
+    get_form_derivations(verbs, verb_id, person, number, case, gender)
+        .iter()
+        .map(|d| d.as_ref().map(|steps| hc_derivation_form(steps)))
+        .collect()
+}
+
+//the last step is the finished form
+fn hc_derivation_form(steps: &[DerivationStep]) -> String {
+    steps.last().unwrap().form.replace(" /", ",")
+}
+
+//same forms as get_forms(), with the steps that build each one
+pub fn get_form_derivations(
+    verbs: &[Arc<HcGreekVerb>],
+    verb_id: usize,
+    person: i32,
+    number: i32,
+    case: Option<i32>,
+    gender: Option<i32>,
+) -> Vec<Option<Vec<DerivationStep>>> {
     let mut forms = Vec::new();
 
     let tenses = [
@@ -943,7 +853,7 @@ pub fn get_forms(
                         };
 
                         if let Ok(f) = vf.get_form(false) {
-                            forms.push(Some(f.last().unwrap().form.replace(" /", ",")))
+                            forms.push(Some(hc_derivation(&f)))
                         } else {
                             forms.push(None)
                         }
@@ -954,3 +864,453 @@ pub fn get_forms(
     }
     forms
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParadigmQuery {
+    pub verb: usize,
+}
+
+//one cell of a paradigm: person and number, or gender, case and number for participles
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ParadigmForm {
+    pub person: Option<i16>,
+    pub number: Option<i16>,
+    pub gender: Option<i16>,
+    pub case: Option<i16>,
+    pub label: String,
+    pub form: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ParadigmSection {
+    pub tense: i16,
+    pub voice: i16,
+    pub mood: i16,
+    pub label: String,
+    pub forms: Vec<ParadigmForm>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ParadigmResult {
+    pub verb_id: i32,
+    pub lemma: String,
+    pub pps: String,
+    pub sections: Vec<ParadigmSection>,
+}
+
+//the full conjugation of a verb: every person and number of each tense, voice and mood,
+//then infinitives and participles in every gender and case.
+//tenses, voices and moods the verb doesn't have are left out
+pub fn get_paradigm(verbs: &[Arc<HcGreekVerb>], verb_id: usize) -> Option<ParadigmResult> {
+    //verbs[0] is a placeholder
+    if verb_id == 0 || verb_id >= verbs.len() {
+        return None;
+    }
+
+    let tenses = [
+        HcTense::Present,
+        HcTense::Imperfect,
+        HcTense::Future,
+        HcTense::Aorist,
+        HcTense::Perfect,
+        HcTense::Pluperfect,
+        HcTense::FuturePerfect,
+    ];
+    let voices = [HcVoice::Active, HcVoice::Middle, HcVoice::Passive];
+    let moods = [
+        HcMood::Indicative,
+        HcMood::Subjunctive,
+        HcMood::Optative,
+        HcMood::Imperative,
+        HcMood::Infinitive,
+        HcMood::Participle,
+    ];
+    let numbers = [HcNumber::Singular, HcNumber::Plural];
+    let persons = [HcPerson::First, HcPerson::Second, HcPerson::Third];
+    let genders = [HcGender::Masculine, HcGender::Feminine, HcGender::Neuter];
+    let cases = [
+        HcCase::Nominative,
+        HcCase::Genitive,
+        HcCase::Dative,
+        HcCase::Accusative,
+        HcCase::Vocative,
+    ];
+
+    let mut sections = vec![];
+    for (m, t, v) in iproduct!(moods, tenses, voices) {
+        let cells = match m {
+            HcMood::Infinitive => vec![(None, None, None, None)],
+            HcMood::Participle => iproduct!(
+                genders.into_iter().enumerate(),
+                cases.into_iter().enumerate(),
+                numbers
+            )
+            .map(|(g, c, n)| (None, Some(n), Some(g), Some(c)))
+            .collect(),
+            _ => iproduct!(numbers, persons)
+                .map(|(n, p)| (Some(p), Some(n), None, None))
+                .collect(),
+        };
+
+        let forms = cells
+            .into_iter()
+            .map(|(p, n, g, c)| {
+                let vf = HcGreekVerbForm {
+                    verb: verbs[verb_id].clone(),
+                    person: p,
+                    number: n,
+                    tense: t,
+                    voice: v,
+                    mood: m,
+                    gender: g.map(|(_, g)| g),
+                    case: c.map(|(_, c)| c),
+                };
+                let person = p.map(|p| p.to_i16());
+                let number = n.map(|n| n.to_i16());
+                let gender = g.map(|(g, _)| g as i16);
+                let case = c.map(|(c, _)| c as i16);
+                ParadigmForm {
+                    person,
+                    number,
+                    gender,
+                    case,
+                    label: [
+                        hc_param_label(&PERSON_LABELS, person),
+                        hc_param_label(&GENDER_LABELS, gender),
+                        hc_param_label(&CASE_LABELS, case),
+                        hc_param_label(&NUMBER_LABELS, number),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .join(" "),
+                    form: vf
+                        .get_form(false)
+                        .ok()
+                        .map(|f| f.last().unwrap().form.replace(" /", ",")),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if forms.iter().any(|f| f.form.is_some()) {
+            sections.push(ParadigmSection {
+                tense: t.to_i16(),
+                voice: v.to_i16(),
+                mood: m.to_i16(),
+                label: [
+                    hc_param_label(&TENSE_LABELS, Some(t.to_i16())),
+                    hc_param_label(&VOICE_LABELS, Some(v.to_i16())),
+                    hc_param_label(&MOOD_LABELS, Some(m.to_i16())),
+                ]
+                .into_iter()
+                .flatten()
+                .join(" "),
+                forms,
+            });
+        }
+    }
+
+    Some(ParadigmResult {
+        verb_id: verb_id as i32,
+        lemma: verbs[verb_id].pps[0].clone(),
+        pps: verbs[verb_id].pps.join(", "),
+        sections,
+    })
+}
+
+//the six forms of a paradigm drill: singular first to third person, then plural
+pub fn get_paradigm_derivations(
+    verbs: &[Arc<HcGreekVerb>],
+    verb_id: usize,
+    tense: i32,
+    voice: i32,
+    mood: i32,
+) -> Vec<Option<Vec<DerivationStep>>> {
+    let numbers = [HcNumber::Singular, HcNumber::Plural];
+    let persons = [HcPerson::First, HcPerson::Second, HcPerson::Third];
+
+    iproduct!(numbers, persons)
+        .map(|(n, p)| {
+            let vf = HcGreekVerbForm {
+                verb: verbs[verb_id].clone(),
+                person: Some(p),
+                number: Some(n),
+                tense: HcTense::from_i16(tense as i16),
+                voice: HcVoice::from_i16(voice as i16),
+                mood: HcMood::from_i16(mood as i16),
+                gender: None,
+                case: None,
+            };
+            vf.get_form(false).ok().map(|f| hc_derivation(&f))
+        })
+        .collect()
+}
+
+pub async fn save_paradigm(
+    payload: SynopsisSaverRequest,
+    user_id: Option<Uuid>,
+    verbs: &[Arc<HcGreekVerb>],
+    form_index: Option<&FormIndex>,
+    hcdb: &dyn HcDb,
+) -> Result<SynopsisJsonResult, Box<dyn std::error::Error>> {
+    let verb_id: usize = payload.verb.try_into()?;
+    if verb_id == 0 || verb_id >= verbs.len() {
+        return Err("invalid verb".into());
+    }
+    let (tense, voice, mood) = match (payload.tense, payload.voice, payload.mood) {
+        (Some(t), Some(v), Some(m)) => (t, v, m),
+        _ => return Err("missing tense, voice or mood".into()),
+    };
+
+    let derivations = get_paradigm_derivations(verbs, verb_id, tense, voice, mood);
+    let (res_forms, score) = grade_forms(&payload.r, derivations, verbs, form_index);
+    if res_forms.len() != 6 {
+        return Err("a paradigm has six forms".into());
+    }
+
+    let is_correct_pps: Vec<bool> = check_pps(&payload.pp, &verbs[verb_id]);
+
+    let res = SynopsisJsonResult {
+        verb_id: payload.verb,
+        person: payload.person,
+        number: payload.number,
+        case: None,
+        gender: None,
+        unit: payload.unit,
+        pp: payload.pp.clone(),
+        pp_correct: hc_pp_correct(&verbs[verb_id]),
+        pp_is_correct: is_correct_pps
+            .into_iter()
+            .map(|x| (x as i32).to_string())
+            .join(","),
+        name: payload.sname.clone(),
+        advisor: payload.advisor.clone(),
+        score: Some(score),
+        tense: Some(tense),
+        voice: Some(voice),
+        mood: Some(mood),
+        f: res_forms,
+    };
+
+    let mut tx = hcdb.begin_tx().await?;
+    tx.greek_insert_paradigm(user_id, &res, &hc_grades_string(&res.f), &score.to_string())
+        .await?;
+    tx.commit_tx().await?;
+
+    Ok(res)
+}
+
+pub async fn get_paradigm_result(id: Uuid, hcdb: &dyn HcDb) -> Option<SynopsisJsonResult> {
+    let mut tx = hcdb.begin_tx().await.ok()?;
+    let result = tx.greek_get_paradigm_result(id).await.ok()?;
+    tx.commit_tx().await.ok()?;
+
+    let mut res_forms = [
+        (result.f0, result.a0, result.c0),
+        (result.f1, result.a1, result.c1),
+        (result.f2, result.a2, result.c2),
+        (result.f3, result.a3, result.c3),
+        (result.f4, result.a4, result.c4),
+        (result.f5, result.a5, result.c5),
+    ]
+    .into_iter()
+    .map(|(given, correct, is_correct)| SaverResults::new(given, correct, is_correct))
+    .collect::<Vec<_>>();
+    for (f, g) in res_forms.iter_mut().zip(result.grades.split(',')) {
+        f.grade = g.parse::<i32>().ok().map(AnswerGrade::from_i32);
+    }
+
+    Some(SynopsisJsonResult {
+        verb_id: result.selectedverb.parse::<i32>().ok()?,
+        person: 0,
+        number: 0,
+        case: None,
+        gender: None,
+        unit: result.sgiday,
+        pp: result.pp,
+        pp_correct: result.pp_correct,
+        pp_is_correct: result.pp_is_correct,
+        name: result.sname,
+        advisor: result.advisor,
+        score: result.score.parse::<f64>().ok(),
+        tense: Some(result.verbtense),
+        voice: Some(result.verbvoice),
+        mood: Some(result.verbmood),
+        f: res_forms,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PpQuestionQuery {
+    pub units: String, //comma separated textbook units to draw verbs from
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PpQuestion {
+    pub verb_id: i32,
+    pub unit: i32,
+    pub lemma: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PpAnswerQuery {
+    pub verb: i32,
+    pub pp: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PpAnswerResult {
+    pub verb_id: i32,
+    pub unit: i32,
+    pub lemma: String,
+    pub pp: String,
+    pub pp_correct: String,
+    pub pp_is_correct: Vec<bool>,
+    pub score: i32, //number of principal parts right, out of six
+    pub is_correct: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct GreekPpResult {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub verb_id: i32,
+    pub unit: i32,
+    pub pp: String,
+    pub pp_correct: String,
+    pub pp_is_correct: String,
+    pub score: i32,
+}
+
+//how a user has done on one verb's principal parts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PpVerbStats {
+    pub verb_id: i32,
+    pub unit: i32,
+    pub lemma: String,
+    pub attempts: i32,
+    pub correct: i32, //attempts with all six parts right
+}
+
+//a verb from one of the units, favoring verbs the user has gotten wrong or not yet tried
+pub fn get_pp_question(
+    verbs: &[Arc<HcGreekVerb>],
+    units: &str,
+    results: &[GreekPpResult],
+) -> Option<PpQuestion> {
+    let units = units
+        .split(',')
+        .filter_map(|u| u.trim().parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    let stats = get_pp_stats(verbs, results);
+
+    //verbs[0] is a placeholder
+    let candidates = verbs
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, v)| units.contains(&v.hq_unit))
+        .collect::<Vec<_>>();
+    let (verb_id, verb) = candidates
+        .choose_weighted(&mut rand::thread_rng(), |(id, _)| {
+            stats
+                .iter()
+                .find(|s| s.verb_id == *id as i32)
+                .map_or(2.0, |s| {
+                    (s.attempts - s.correct + 1) as f64 / (s.correct + 1) as f64
+                })
+        })
+        .ok()?;
+
+    Some(PpQuestion {
+        verb_id: *verb_id as i32,
+        unit: verb.hq_unit as i32,
+        lemma: hc_verb_option(verb).verb,
+    })
+}
+
+pub async fn pp_question(
+    info: &PpQuestionQuery,
+    user_id: Option<Uuid>,
+    verbs: &[Arc<HcGreekVerb>],
+    hcdb: &dyn HcDb,
+) -> Result<Option<PpQuestion>, HcError> {
+    let results = match user_id {
+        Some(user_id) => {
+            let mut tx = hcdb.begin_tx().await?;
+            let results = tx.greek_get_pp_results(user_id).await?;
+            tx.commit_tx().await?;
+            results
+        }
+        None => vec![],
+    };
+    Ok(get_pp_question(verbs, &info.units, &results))
+}
+
+//grades each of the six principal parts with check_pps and records the attempt
+pub async fn save_pp_answer(
+    info: &PpAnswerQuery,
+    user_id: Option<Uuid>,
+    verbs: &[Arc<HcGreekVerb>],
+    hcdb: &dyn HcDb,
+) -> Result<PpAnswerResult, Box<dyn std::error::Error>> {
+    let verb_id: usize = info.verb.try_into()?;
+    if verb_id == 0 || verb_id >= verbs.len() {
+        return Err("invalid verb".into());
+    }
+    let verb = &verbs[verb_id];
+
+    let pp_is_correct = check_pps(&info.pp, verb);
+    let score = pp_is_correct.iter().filter(|c| **c).count() as i32;
+    let res = PpAnswerResult {
+        verb_id: info.verb,
+        unit: verb.hq_unit as i32,
+        lemma: hc_verb_option(verb).verb,
+        pp: info.pp.clone(),
+        pp_correct: hc_pp_correct(verb),
+        is_correct: score == 6,
+        pp_is_correct,
+        score,
+    };
+
+    let mut tx = hcdb.begin_tx().await?;
+    tx.greek_insert_pp_result(user_id, &res).await?;
+    tx.commit_tx().await?;
+
+    Ok(res)
+}
+
+//one row per verb attempted, in textbook order
+pub fn get_pp_stats(verbs: &[Arc<HcGreekVerb>], results: &[GreekPpResult]) -> Vec<PpVerbStats> {
+    let mut stats: Vec<PpVerbStats> = vec![];
+    for r in results {
+        match stats.iter_mut().find(|s| s.verb_id == r.verb_id) {
+            Some(s) => {
+                s.attempts += 1;
+                s.correct += (r.score == 6) as i32;
+            }
+            None => stats.push(PpVerbStats {
+                verb_id: r.verb_id,
+                unit: r.unit,
+                lemma: verbs
+                    .get(r.verb_id as usize)
+                    .map(|v| hc_verb_option(v).verb)
+                    .unwrap_or_default(),
+                attempts: 1,
+                correct: (r.score == 6) as i32,
+            }),
+        }
+    }
+    stats.sort_by_key(|s| (s.unit, s.verb_id));
+    stats
+}
+
+pub async fn pp_stats(
+    user_id: Uuid,
+    verbs: &[Arc<HcGreekVerb>],
+    hcdb: &dyn HcDb,
+) -> Result<Vec<PpVerbStats>, HcError> {
+    let mut tx = hcdb.begin_tx().await?;
+    let results = tx.greek_get_pp_results(user_id).await?;
+    tx.commit_tx().await?;
+    Ok(get_pp_stats(verbs, &results))
+}